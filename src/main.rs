@@ -15,7 +15,24 @@ fn main() {
 
     let args: Vec<String> = std::env::args().collect();
 
-    let (args, user_input) = MusicPlayerOptions::extract_user_input_url(args);
+    let (args, download_inputs) = MusicPlayerOptions::extract_download_inputs(args);
+    let (args, user_input) = if download_inputs.is_none() {
+        MusicPlayerOptions::extract_user_input_url(args)
+    } else {
+        (args, None)
+    };
+    let (args, search_query) = if user_input.is_none() {
+        match MusicPlayerOptions::extract_search_query(args) {
+            Ok(extracted) => extracted,
+            Err(err) => {
+                println!("{:?}", err);
+                log::error!("{:?}", err);
+                return;
+            }
+        }
+    } else {
+        (args, None)
+    };
     let args = &args[1..];
 
     let music_player_options = MusicPlayerOptions::new();
@@ -29,8 +46,17 @@ fn main() {
         .unwrap();
 
     let overwrite_config = options.contains(&OptionAction::OverwriteConfig);
+    let resume = options.contains(&OptionAction::Resume);
+    let offline = options.contains(&OptionAction::Offline);
 
-    if options.contains(&OptionAction::PrintHelp) || (user_input.is_none() && !overwrite_config) {
+    if options.contains(&OptionAction::PrintHelp)
+        || (user_input.is_none()
+            && search_query.is_none()
+            && download_inputs.is_none()
+            && !overwrite_config
+            && !resume
+            && !offline)
+    {
         MusicPlayerOptions::new().print_help();
         return;
     }
@@ -46,7 +72,15 @@ fn main() {
 
         crossbeam::scope(|scope| {
             scope.spawn(|_| -> Result<(), Error> {
-                let result = music_player(config, options, overwrite_config, user_input);
+                let result = music_player(
+                    config,
+                    options,
+                    overwrite_config,
+                    resume,
+                    user_input,
+                    search_query,
+                    download_inputs,
+                );
                 log_send.send_quit_signal();
 
                 result
@@ -66,7 +100,10 @@ fn music_player(
     mut config: MusicPlayerConfig,
     options: Vec<OptionAction>,
     overwrite_config: bool,
+    resume: bool,
     user_input: Option<String>,
+    search_query: Option<String>,
+    download_inputs: Option<Vec<String>>,
 ) -> Result<(), Error> {
     config.apply_complex_actions(options).map_err(|err| {
         println!("{:?}", err);
@@ -80,6 +117,31 @@ fn music_player(
         err
     })?;
 
+    config.validate().map_err(|err| {
+        println!("{:?}", err);
+        log::error!("{:?}", err);
+        err
+    })?;
+
+    if let Some(download_inputs) = download_inputs {
+        return MusicPlayerOptions::download_sources(&config, &download_inputs).map_err(|err| {
+            println!("{:?}", err);
+            log::error!("{:?}", err);
+            err
+        });
+    }
+
+    let user_input = match search_query {
+        Some(search_query) => Some(
+            MusicPlayerOptions::resolve_search_query(&config, &search_query).map_err(|err| {
+                println!("{:?}", err);
+                log::error!("{:?}", err);
+                err
+            })?,
+        ),
+        None => user_input,
+    };
+
     if let Some(user_input) = user_input {
         let mut music_player = MusicPlayer::new(config);
 
@@ -88,6 +150,26 @@ fn music_player(
             log::error!("{:?}", err);
             err
         })?;
+    } else if resume {
+        let mut music_player = MusicPlayer::new_resumed(config).map_err(|err| {
+            println!("{:?}", err);
+            log::error!("{:?}", err);
+            err
+        })?;
+
+        music_player.resume().map_err(|err| {
+            println!("{:?}", err);
+            log::error!("{:?}", err);
+            err
+        })?;
+    } else if config.offline {
+        let mut music_player = MusicPlayer::new(config);
+
+        music_player.play_cached().map_err(|err| {
+            println!("{:?}", err);
+            log::error!("{:?}", err);
+            err
+        })?;
     }
     Ok(())
 }
@@ -75,21 +75,48 @@ impl MusicPlayer {
         }
     }
 
-    pub fn play(&mut self, user_input: &str) -> Result<(), Error> {
-        let ev_ctx = self.libmpv.create_event_context();
-        let ev_ctx = ev_ctx.unwrap();
+    pub fn new_resumed(mut config: music_player_config::MusicPlayerConfig) -> Result<Self, Error> {
+        let session = music_player_core::session::SessionState::load(&config.session_path)?;
+        config.mpv_base_volume = session.volume;
 
-        let mut error: Result<(), Error> = self.music_player_logic.validate_user_input(user_input);
+        let mut music_player = Self::new(config);
+        music_player.music_player_logic.set_session(session);
+
+        Ok(music_player)
+    }
+
+    pub fn play(&mut self, user_input: &str) -> Result<(), Error> {
+        let error: Result<(), Error> = self.music_player_logic.validate_user_input(user_input);
         if error.is_err() {
             self.tui.restore_terminal();
             return error;
         }
+
+        self.run(|music_player_logic| music_player_logic.process_user_input(user_input))
+    }
+
+    pub fn play_cached(&mut self) -> Result<(), Error> {
+        self.run(|music_player_logic| music_player_logic.prepare_audio_cache())
+    }
+
+    pub fn resume(&mut self) -> Result<(), Error> {
+        self.run(|music_player_logic| music_player_logic.restore_session())
+    }
+
+    fn run<F>(&mut self, prepare_to_play: F) -> Result<(), Error>
+    where
+        F: FnOnce(&mut music_player_core::MusicPlayerLogic) -> Result<(), Error> + Send,
+    {
+        let ev_ctx = self.libmpv.create_event_context();
+        let ev_ctx = ev_ctx.unwrap();
+
+        let mut error: Result<(), Error> = Ok(());
         crossbeam::scope(|scope| {
             scope.spawn(|_| self.libmpv.handle_signals());
             scope.spawn(|_| self.tui.handle_signals());
             scope.spawn(|_| self.libmpv_event_handler.libmpv_event_handling(ev_ctx));
             scope.spawn(|_| {
-                error = self.music_player_logic.process_user_input(user_input);
+                error = prepare_to_play(&mut self.music_player_logic);
                 if error.is_ok() {
                     error = self.music_player_logic.handle_playback_logic();
                 }
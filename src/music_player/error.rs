@@ -2,6 +2,12 @@
 pub enum Error {
     InvalidVideoUrl(String),
     InvalidPlaylistUrl(String),
+    InvalidLocalPath(String),
+    InvalidStreamUrl(String),
+    InvalidPlaylistFile(String),
+    InvalidSessionFile(String),
+    InvalidSearchQuery(String),
+    InvalidConfig(String),
     ReqwestError(reqwest::Error),
     VideoBlockedInAllRegions,
     VideoBlockedOnCopyRightGrounds,
@@ -11,6 +17,7 @@ pub enum Error {
     StdIOError(std::io::Error),
     OtherError(String),
     SerdeJSONError(serde_json::Error),
+    SymphoniaError(symphonia::core::errors::Error),
     PrintHelp,
     InvalidOption(String),
 }
@@ -32,3 +39,9 @@ impl From<serde_json::Error> for Error {
         Error::SerdeJSONError(err)
     }
 }
+
+impl From<symphonia::core::errors::Error> for Error {
+    fn from(err: symphonia::core::errors::Error) -> Self {
+        Error::SymphoniaError(err)
+    }
+}
@@ -1,17 +1,53 @@
-use crate::music_player::music_player_core::MusicPlayerLogicSignals;
+use crate::music_player::music_player_core::{MusicPlayerLogicSignals, RepeatMode};
 use crate::music_player::tui::TuiSignals;
 
 #[derive(Debug)]
 pub enum LibMpvSignals {
-    RemoveBrokenItem(usize),
+    RemoveItem(usize),
+    ReplaceItem(usize, String),
+    TruncatePlaylist(usize),
+    PlayIndex(usize),
     AddAudio(String),
     PlayNext,
     PlayPrev,
     PauseResume,
     SetVolume(i64),
+    Seek(SeekTarget),
+    SetRepeat(RepeatMode),
+    SkipSegment(f64, f64),
     End,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub enum SeekTarget {
+    Relative(f64),
+    Absolute(f64),
+    Percent(f64),
+}
+
+impl SeekTarget {
+    pub fn parse(target: &str) -> Option<Self> {
+        if let Some(percent) = target.strip_suffix('%') {
+            let percent: f64 = percent.parse().ok()?;
+            return Some(SeekTarget::Percent(percent.clamp(0.0, 100.0)));
+        }
+        if target.starts_with('+') || target.starts_with('-') {
+            return Some(SeekTarget::Relative(target.parse().ok()?));
+        }
+
+        let mut seconds = 0.0;
+        for part in target.split(':') {
+            let part: f64 = part.parse().ok()?;
+            if part < 0.0 {
+                return None;
+            }
+            seconds = seconds * 60.0 + part;
+        }
+
+        Some(SeekTarget::Absolute(seconds))
+    }
+}
+
 const MPV_ERROR_LOADING_FAILED: libmpv::Error = libmpv::Error::Raw(-13);
 
 pub struct LibMpvHandler {
@@ -40,6 +76,15 @@ impl LibMpvHandler {
         ev_ctx
             .observe_property("pause", libmpv::Format::Flag, 0)
             .unwrap();
+        ev_ctx
+            .observe_property("time-pos", libmpv::Format::Double, 1)
+            .unwrap();
+        ev_ctx
+            .observe_property("duration", libmpv::Format::Double, 2)
+            .unwrap();
+        ev_ctx
+            .observe_property("paused-for-cache", libmpv::Format::Flag, 3)
+            .unwrap();
 
         Ok(ev_ctx)
     }
@@ -81,13 +126,71 @@ impl LibMpvHandler {
                         LibMpvSignals::SetVolume(vol) => {
                             self.mpv.set_property("volume", vol).unwrap();
                         }
+                        LibMpvSignals::SkipSegment(start, end) => {
+                            let position: f64 = self.mpv.get_property("time-pos").unwrap_or(0.0);
+                            if position >= start && position < end {
+                                let result = self.mpv.seek_absolute(end);
+                                if result.is_err() {
+                                    log::info!("LibMpvHandler::handle_signals -> {:?}", result);
+                                }
+                            }
+                        }
+                        LibMpvSignals::SetRepeat(repeat) => {
+                            let (loop_file, loop_playlist) = match repeat {
+                                RepeatMode::One => ("inf", "no"),
+                                RepeatMode::All => ("no", "inf"),
+                                RepeatMode::Off | RepeatMode::Related => ("no", "no"),
+                            };
+                            self.mpv.set_property("loop-file", loop_file).unwrap();
+                            self.mpv
+                                .set_property("loop-playlist", loop_playlist)
+                                .unwrap();
+                        }
+                        LibMpvSignals::Seek(target) => {
+                            let result = match target {
+                                SeekTarget::Relative(secs) => {
+                                    self.mpv.command("seek", &[&secs.to_string(), "relative"])
+                                }
+                                SeekTarget::Absolute(secs) => self.mpv.seek_absolute(secs),
+                                SeekTarget::Percent(percent) => self
+                                    .mpv
+                                    .command("seek", &[&percent.to_string(), "absolute-percent"]),
+                            };
+                            if result.is_err() {
+                                log::info!("LibMpvHandler::handle_signals -> {:?}", result);
+                            }
+                        }
                         LibMpvSignals::End => {
                             self.mpv.command("quit", &["0"]).unwrap();
                             break;
                         }
-                        LibMpvSignals::RemoveBrokenItem(index) => {
+                        LibMpvSignals::RemoveItem(index) => {
                             self.mpv.playlist_remove_index(index).unwrap();
                         }
+                        LibMpvSignals::ReplaceItem(index, source) => {
+                            self.mpv
+                                .playlist_load_files(&[(&source, libmpv::FileState::Append, None)])
+                                .unwrap();
+                            let count: i64 = self.mpv.get_property("playlist-count").unwrap_or(0);
+                            self.mpv
+                                .command(
+                                    "playlist-move",
+                                    &[&(count - 1).to_string(), &index.to_string()],
+                                )
+                                .unwrap();
+                            self.mpv.playlist_remove_index(index + 1).unwrap();
+                        }
+                        LibMpvSignals::TruncatePlaylist(len) => {
+                            let count: i64 = self.mpv.get_property("playlist-count").unwrap_or(0);
+                            for index in (len..count as usize).rev() {
+                                self.mpv.playlist_remove_index(index).unwrap();
+                            }
+                        }
+                        LibMpvSignals::PlayIndex(index) => {
+                            self.mpv
+                                .command("playlist-play-index", &[&index.to_string()])
+                                .unwrap();
+                        }
                     }
                 }
             }
@@ -98,6 +201,7 @@ impl LibMpvHandler {
 pub struct EventHandler {
     mp_logic_signal_send: crossbeam::channel::Sender<MusicPlayerLogicSignals>,
     tui_signal_send: crossbeam::channel::Sender<TuiSignals>,
+    playback_time: Option<u64>,
 }
 
 impl EventHandler {
@@ -108,16 +212,25 @@ impl EventHandler {
         Self {
             mp_logic_signal_send,
             tui_signal_send,
+            playback_time: None,
         }
     }
 
-    pub fn libmpv_event_handling(&self, mut ev_ctx: libmpv::events::EventContext) {
+    pub fn libmpv_event_handling(&mut self, mut ev_ctx: libmpv::events::EventContext) {
         loop {
             let ev = ev_ctx.wait_event(600.).unwrap_or(Err(libmpv::Error::Null));
 
             match ev {
                 Ok(event) => {
-                    log::info!("EventHandler::libmpv_event_handling -> {:?}", event);
+                    if !matches!(
+                        event,
+                        libmpv::events::Event::PropertyChange {
+                            name: "time-pos",
+                            ..
+                        }
+                    ) {
+                        log::info!("EventHandler::libmpv_event_handling -> {:?}", event);
+                    }
                     let end = self.handle_event(event);
                     if end {
                         break;
@@ -139,12 +252,13 @@ impl EventHandler {
         }
     }
 
-    fn handle_event(&self, event: libmpv::events::Event) -> bool {
+    fn handle_event(&mut self, event: libmpv::events::Event) -> bool {
         match event {
-            libmpv::events::Event::EndFile(_r) => {
+            libmpv::events::Event::EndFile(reason) => {
+                self.playback_time = None;
                 self.tui_signal_send.send(TuiSignals::End).unwrap();
                 self.mp_logic_signal_send
-                    .send(MusicPlayerLogicSignals::PlaybackEnded)
+                    .send(MusicPlayerLogicSignals::PlaybackEnded(reason))
                     .unwrap();
             }
             libmpv::events::Event::PropertyChange {
@@ -156,12 +270,52 @@ impl EventHandler {
                     self.tui_signal_send
                         .send(TuiSignals::PlaybackPause)
                         .unwrap();
+                    self.mp_logic_signal_send
+                        .send(MusicPlayerLogicSignals::PlaybackPause)
+                        .unwrap();
                 } else {
                     self.tui_signal_send
                         .send(TuiSignals::PlaybackResume)
                         .unwrap();
+                    self.mp_logic_signal_send
+                        .send(MusicPlayerLogicSignals::PlaybackResume)
+                        .unwrap();
                 }
             }
+            libmpv::events::Event::PropertyChange {
+                name: "time-pos",
+                change: libmpv::events::PropertyData::Double(time_pos),
+                ..
+            } => {
+                let playback_time = time_pos.max(0.0) as u64;
+                if self.playback_time != Some(playback_time) {
+                    self.playback_time = Some(playback_time);
+                    self.tui_signal_send
+                        .send(TuiSignals::UpdatePlaybackTime(time_pos))
+                        .unwrap();
+                    self.mp_logic_signal_send
+                        .send(MusicPlayerLogicSignals::PlaybackTime(time_pos))
+                        .unwrap();
+                }
+            }
+            libmpv::events::Event::PropertyChange {
+                name: "duration",
+                change: libmpv::events::PropertyData::Double(duration),
+                ..
+            } => {
+                self.tui_signal_send
+                    .send(TuiSignals::UpdatePlaybackDuration(duration))
+                    .unwrap();
+            }
+            libmpv::events::Event::PropertyChange {
+                name: "paused-for-cache",
+                change: libmpv::events::PropertyData::Flag(buffering),
+                ..
+            } => {
+                self.tui_signal_send
+                    .send(TuiSignals::UpdateBuffering(buffering))
+                    .unwrap();
+            }
 
             libmpv::events::Event::PropertyChange {
                 name: "demuxer-cache-state",
@@ -174,12 +328,14 @@ impl EventHandler {
             libmpv::events::Event::StartFile => {
                 self.tui_signal_send.send(TuiSignals::Start).unwrap();
             }
-            libmpv::events::Event::PlaybackRestart => {
-                self.tui_signal_send.send(TuiSignals::AudioReady).unwrap();
+            libmpv::events::Event::FileLoaded => {
                 self.mp_logic_signal_send
                     .send(MusicPlayerLogicSignals::PrepareNextFile)
                     .unwrap();
             }
+            libmpv::events::Event::PlaybackRestart => {
+                self.tui_signal_send.send(TuiSignals::AudioReady).unwrap();
+            }
             libmpv::events::Event::Shutdown => {
                 return true;
             }
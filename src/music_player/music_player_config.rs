@@ -1,11 +1,22 @@
 use crate::music_player::tui::commands::commands_registry::Arg;
 use crate::music_player::Error;
 pub mod options_registry;
-use crate::music_player::music_player_core::music_source::Source;
+use crate::music_player::music_player_core::music_source::audio_stream::AudioStreamPreferences;
+use crate::music_player::music_player_core::music_source::autoplay_filter::{
+    AutoplayFilter, AutoplayFilterConfig,
+};
+use crate::music_player::music_player_core::music_source::download::{
+    self, DownloadConfig, DownloadedFile,
+};
+use crate::music_player::music_player_core::music_source::recommendation::RecommendationConfig;
+use crate::music_player::music_player_core::music_source::{Remote, RemoteSourceProcessor, Source};
+use crate::music_player::music_player_core::RepeatMode;
 use crate::utils;
 use options_registry::{OptionAction, OptionDefinition, OptionType, OptionsRegistry};
+use std::io::Write;
 
 #[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
 pub struct MusicPlayerConfig {
     pub piped_api_domains: Vec<String>,
     pub piped_api_domain_index: usize,
@@ -14,8 +25,43 @@ pub struct MusicPlayerConfig {
     pub invidious_api_domain_index: usize,
     pub mpv_base_volume: i64,
     pub video_duration_limit_s: u64,
+    pub video_duration_min_s: u64,
+    pub accepted_genres: Vec<String>,
+    pub genre_check: bool,
+    pub audio_codec: String,
+    pub audio_max_bitrate_kbps: u64,
+    pub audio_language: String,
+    pub sponsorblock_api: String,
+    pub sponsorblock_categories: Vec<String>,
+    pub lyrics_api: String,
+    pub audio_cache_dir: String,
+    pub audio_cache_max_mb: u64,
+    pub offline: bool,
+    pub download_dir: String,
+    pub download_filename_template: String,
+    pub ffmpeg_path: String,
+    pub prefetch_count: usize,
+    pub prefetch_workers: usize,
     pub debug_log: bool,
     pub play_only_recommendations: bool,
+    pub related_mix_ratio: usize,
+    pub export_m3u8_on_quit: bool,
+    pub export_json_on_quit: bool,
+    pub export_dir: String,
+    pub history_db_path: String,
+    pub history_dedup: bool,
+    pub session_path: String,
+    pub feedback_path: String,
+    pub search_pick: bool,
+    pub repeat: RepeatMode,
+    pub recommendation: RecommendationConfig,
+    pub autoplay_filters: AutoplayFilterConfig,
+}
+
+impl Default for MusicPlayerConfig {
+    fn default() -> Self {
+        serde_json::from_str(MusicPlayerConfig::get_def_conf()).unwrap()
+    }
 }
 
 impl MusicPlayerConfig {
@@ -48,13 +94,69 @@ impl MusicPlayerConfig {
           "invidious_api_domain_index": 0,
           "mpv_base_volume": 100,
           "video_duration_limit_s": 600,
+          "video_duration_min_s": 0,
+          "accepted_genres": [
+            "Music"
+          ],
+          "genre_check": true,
+          "audio_codec": "any",
+          "audio_max_bitrate_kbps": 0,
+          "audio_language": "",
+          "sponsorblock_api": "",
+          "sponsorblock_categories": [
+            "music_offtopic",
+            "sponsor",
+            "intro"
+          ],
+          "lyrics_api": "https://lrclib.net",
+          "audio_cache_dir": "",
+          "audio_cache_max_mb": 1024,
+          "offline": false,
+          "download_dir": "music",
+          "download_filename_template": "{uploader} - {title}",
+          "ffmpeg_path": "ffmpeg",
+          "prefetch_count": 2,
+          "prefetch_workers": 2,
           "shuffle_playlist": true,
           "play_only_recommendations": false,
+          "related_mix_ratio": 0,
+          "export_m3u8_on_quit": false,
+          "export_json_on_quit": false,
+          "export_dir": "",
+          "history_db_path": "history.jsonl",
+          "history_dedup": false,
+          "session_path": "session.json",
+          "feedback_path": "feedback.json",
+          "search_pick": false,
+          "repeat": "related",
+          "recommendation": {
+            "same_uploader_penalty": 0.5,
+            "diversity_window": 10,
+            "diversity_penalty": 0.2,
+            "recency_decay": 0.8,
+            "seeds_count": 5,
+            "randomness": 0.2,
+            "liked_seed_boost": 2.0
+          },
+          "autoplay_filters": {
+            "blocked_uploaders": [],
+            "blocked_channel_ids": [],
+            "blocked_title_patterns": [],
+            "allowed_uploaders": [],
+            "allowed_channel_ids": [],
+            "allowed_title_patterns": []
+          },
           "debug_log": false
         }
         "#
     }
 
+    pub fn validate(&self) -> Result<(), Error> {
+        AutoplayFilter::new(&self.autoplay_filters)?;
+
+        Ok(())
+    }
+
     pub fn apply_simple_actions(&mut self, actions: Vec<OptionAction>) -> Vec<OptionAction> {
         let config = self;
         let mut complex_actions = Vec::new();
@@ -70,12 +172,29 @@ impl MusicPlayerConfig {
                 }
                 OptionAction::SetMpvBaseVolume(val) => config.mpv_base_volume = val,
                 OptionAction::SetVideoDurationLimit(val) => config.video_duration_limit_s = val,
+                OptionAction::SetVideoDurationMin(val) => config.video_duration_min_s = val,
+                OptionAction::SetGenreCheck(val) => config.genre_check = val,
+                OptionAction::SetAudioCodec(val) => config.audio_codec = val,
+                OptionAction::SetAudioMaxBitrate(val) => config.audio_max_bitrate_kbps = val,
+                OptionAction::SetAudioLanguage(val) => config.audio_language = val,
+                OptionAction::SetAudioCacheDir(val) => config.audio_cache_dir = val,
+                OptionAction::SetDownloadDir(val) => config.download_dir = val,
+                OptionAction::SetPrefetchCount(val) => config.prefetch_count = val,
                 OptionAction::SetDebugLog(val) => config.debug_log = val,
                 OptionAction::SetPlayOnlyRecommendations(val) => {
                     config.play_only_recommendations = val
                 }
+                OptionAction::SetRelatedMixRatio(val) => config.related_mix_ratio = val,
+                OptionAction::SetExportM3U8OnQuit(val) => config.export_m3u8_on_quit = val,
+                OptionAction::SetExportJSONOnQuit(val) => config.export_json_on_quit = val,
+                OptionAction::SetExportDir(val) => config.export_dir = val,
+                OptionAction::SetHistoryDedup(val) => config.history_dedup = val,
+                OptionAction::SetSearchPick(val) => config.search_pick = val,
+                OptionAction::SetRepeat(val) => config.repeat = val,
                 OptionAction::PrintHelp => (),
                 OptionAction::OverwriteConfig => (),
+                OptionAction::Resume => (),
+                OptionAction::Offline => config.offline = true,
                 OptionAction::RankPipedApiDomains => {
                     complex_actions.push(OptionAction::RankPipedApiDomains)
                 }
@@ -192,6 +311,31 @@ impl MusicPlayerOptions {
                 option_type: OptionType::SetVideoDurationLimit,
                 args: vec![Arg::U64(None)],
             },
+            OptionDefinition {
+                name: "--video_duration_min_s".to_string(),
+                option_type: OptionType::SetVideoDurationMin,
+                args: vec![Arg::U64(None)],
+            },
+            OptionDefinition {
+                name: "--genre_check".to_string(),
+                option_type: OptionType::SetGenreCheck,
+                args: vec![Arg::BOOL(None)],
+            },
+            OptionDefinition {
+                name: "--audio_codec".to_string(),
+                option_type: OptionType::SetAudioCodec,
+                args: vec![Arg::STRING(None)],
+            },
+            OptionDefinition {
+                name: "--audio_max_bitrate_kbps".to_string(),
+                option_type: OptionType::SetAudioMaxBitrate,
+                args: vec![Arg::U64(None)],
+            },
+            OptionDefinition {
+                name: "--audio_language".to_string(),
+                option_type: OptionType::SetAudioLanguage,
+                args: vec![Arg::STRING(None)],
+            },
             OptionDefinition {
                 name: "--debug_log".to_string(),
                 option_type: OptionType::SetDebugLog,
@@ -202,6 +346,41 @@ impl MusicPlayerOptions {
                 option_type: OptionType::SetPlayOnlyRecommendations,
                 args: vec![Arg::BOOL(None)],
             },
+            OptionDefinition {
+                name: "--related_mix_ratio".to_string(),
+                option_type: OptionType::SetRelatedMixRatio,
+                args: vec![Arg::USIZE(None)],
+            },
+            OptionDefinition {
+                name: "--export_m3u8_on_quit".to_string(),
+                option_type: OptionType::SetExportM3U8OnQuit,
+                args: vec![Arg::BOOL(None)],
+            },
+            OptionDefinition {
+                name: "--export_json_on_quit".to_string(),
+                option_type: OptionType::SetExportJSONOnQuit,
+                args: vec![Arg::BOOL(None)],
+            },
+            OptionDefinition {
+                name: "--export_dir".to_string(),
+                option_type: OptionType::SetExportDir,
+                args: vec![Arg::STRING(None)],
+            },
+            OptionDefinition {
+                name: "--history_dedup".to_string(),
+                option_type: OptionType::SetHistoryDedup,
+                args: vec![Arg::BOOL(None)],
+            },
+            OptionDefinition {
+                name: "--search_pick".to_string(),
+                option_type: OptionType::SetSearchPick,
+                args: vec![Arg::BOOL(None)],
+            },
+            OptionDefinition {
+                name: "--repeat".to_string(),
+                option_type: OptionType::SetRepeat,
+                args: vec![Arg::REPEATMODE(None)],
+            },
             OptionDefinition {
                 name: "--rank_piped_api_domains".to_string(),
                 option_type: OptionType::RankPipedApiDomains,
@@ -222,6 +401,31 @@ impl MusicPlayerOptions {
                 option_type: OptionType::FetchInvidiousApiDomains,
                 args: vec![],
             },
+            OptionDefinition {
+                name: "--audio_cache_dir".to_string(),
+                option_type: OptionType::SetAudioCacheDir,
+                args: vec![Arg::STRING(None)],
+            },
+            OptionDefinition {
+                name: "--download_dir".to_string(),
+                option_type: OptionType::SetDownloadDir,
+                args: vec![Arg::STRING(None)],
+            },
+            OptionDefinition {
+                name: "--prefetch_count".to_string(),
+                option_type: OptionType::SetPrefetchCount,
+                args: vec![Arg::USIZE(None)],
+            },
+            OptionDefinition {
+                name: "--offline".to_string(),
+                option_type: OptionType::Offline,
+                args: vec![],
+            },
+            OptionDefinition {
+                name: "--resume".to_string(),
+                option_type: OptionType::Resume,
+                args: vec![],
+            },
             OptionDefinition {
                 name: "--overwrite_config".to_string(),
                 option_type: OptionType::OverwriteConfig,
@@ -244,6 +448,60 @@ impl MusicPlayerOptions {
         (args, user_input)
     }
 
+    pub fn extract_download_inputs(mut args: Vec<String>) -> (Vec<String>, Option<Vec<String>>) {
+        if args.get(1).map(|arg| arg.as_str()) != Some("download") {
+            return (args, None);
+        }
+        args.remove(1);
+
+        let mut options = vec![args.remove(0)];
+        let mut download_inputs = Vec::new();
+        for arg in args {
+            if arg.starts_with("--") {
+                options.push(arg);
+            } else {
+                download_inputs.push(arg);
+            }
+        }
+
+        if download_inputs.is_empty() {
+            (options, None)
+        } else {
+            (options, Some(download_inputs))
+        }
+    }
+
+    pub fn extract_search_query(
+        mut args: Vec<String>,
+    ) -> Result<(Vec<String>, Option<String>), Error> {
+        if let Some(i) = args.iter().position(|arg| arg == "--search") {
+            if i + 1 < args.len() {
+                let search_query = args.remove(i + 1);
+                args.remove(i);
+                return Ok((args, Some(search_query)));
+            }
+        }
+
+        let last_arg = match args.last() {
+            Some(last_arg) if args.len() > 1 && !last_arg.starts_with("--") => last_arg,
+            _ => return Ok((args, None)),
+        };
+        if last_arg.contains("://") {
+            return Err(Error::InvalidVideoUrl(format!(
+                "Unsupported url: {last_arg}"
+            )));
+        }
+        if Source::looks_like_path(last_arg) {
+            return Err(Error::InvalidLocalPath(format!(
+                "No such file or directory: {last_arg}"
+            )));
+        }
+
+        let search_query = args.pop();
+
+        Ok((args, search_query))
+    }
+
     pub fn preprocess_args(&self, args: &[String]) -> Result<Vec<OptionAction>, Error> {
         let mut actions = Vec::new();
 
@@ -284,7 +542,12 @@ impl MusicPlayerOptions {
     }
 
     pub fn print_help(&self) {
-        println!("Usage: rustunes [OPTIONS] URL");
+        println!("Usage: rustunes [OPTIONS] URL|FILE|DIR|PLAYLIST_FILE");
+        println!("       rustunes [OPTIONS] --search QUERY");
+        println!("       rustunes [OPTIONS] QUERY");
+        println!("       rustunes --resume [OPTIONS]");
+        println!("       rustunes --offline [OPTIONS]");
+        println!("       rustunes download [OPTIONS] URL|PLAYLIST...");
         println!("       rustunes --overwrite_config [OPTIONS]");
         println!("");
         println!("Options:");
@@ -295,6 +558,167 @@ impl MusicPlayerOptions {
             .for_each(|option| println!("  {option}"));
     }
 
+    pub fn resolve_search_query(config: &MusicPlayerConfig, query: &str) -> Result<String, Error> {
+        println!("Searching: {query}");
+        log::info!("MusicPlayerOptions::resolve_search_query -> {query}");
+
+        let mut remote_src_proc = RemoteSourceProcessor::new(
+            config.piped_api_domains.clone(),
+            config.piped_api_domain_index,
+            config.invidious_api_domains.clone(),
+            config.invidious_api_domain_index,
+            config.video_duration_limit_s,
+        )?;
+
+        let mut results = remote_src_proc.search(query);
+        while results.is_err() {
+            remote_src_proc.next_piped_api_domains_index()?;
+            results = remote_src_proc.search(query);
+        }
+        let results = results?;
+
+        if results.is_empty() {
+            return Err(Error::InvalidSearchQuery(format!(
+                "No results found for: {query}"
+            )));
+        }
+
+        if !config.search_pick {
+            println!("Playing: {}", results[0].to_display_str());
+            return Ok(results[0].url.clone());
+        }
+
+        for (i, result) in results.iter().enumerate() {
+            println!("  {i}: {}", result.to_display_str());
+        }
+
+        let stdin = std::io::stdin();
+        loop {
+            print!("Select [0-{}]: ", results.len() - 1);
+            std::io::stdout().flush()?;
+
+            let mut selection = String::new();
+            if stdin.read_line(&mut selection)? == 0 {
+                return Err(Error::InvalidSearchQuery(
+                    "No search result selected".to_string(),
+                ));
+            }
+            let selection = selection.trim();
+            let selection = if selection.is_empty() {
+                Some(0)
+            } else {
+                selection.parse::<usize>().ok()
+            };
+
+            if let Some(result) = selection.and_then(|i| results.get(i)) {
+                log::info!(
+                    "MusicPlayerOptions::resolve_search_query -> {:?}",
+                    result.url
+                );
+                return Ok(result.url.clone());
+            }
+        }
+    }
+
+    pub fn download_sources(
+        config: &MusicPlayerConfig,
+        download_inputs: &[String],
+    ) -> Result<(), Error> {
+        log::info!(
+            "MusicPlayerOptions::download_sources -> {:?}",
+            download_inputs
+        );
+
+        let mut remote_src_proc = RemoteSourceProcessor::new(
+            config.piped_api_domains.clone(),
+            config.piped_api_domain_index,
+            config.invidious_api_domains.clone(),
+            config.invidious_api_domain_index,
+            config.video_duration_limit_s,
+        )?;
+        remote_src_proc.set_audio_preferences(AudioStreamPreferences {
+            codec: config.audio_codec.clone(),
+            max_bitrate_kbps: config.audio_max_bitrate_kbps,
+            language: config.audio_language.clone(),
+        });
+        let download_config = DownloadConfig {
+            dir: config.download_dir.clone(),
+            filename_template: config.download_filename_template.clone(),
+            ffmpeg_path: config.ffmpeg_path.clone(),
+        };
+
+        let mut sources = Vec::new();
+        for download_input in download_inputs {
+            if download_input.contains("list=") {
+                let playlist_id = Remote::url_into_playlist_id(download_input)?;
+                let mut playlist = remote_src_proc.playlist_to_remote_vec(&playlist_id);
+                while playlist.is_err() {
+                    remote_src_proc.next_piped_api_domains_index()?;
+                    playlist = remote_src_proc.playlist_to_remote_vec(&playlist_id);
+                }
+                sources.extend(playlist?);
+            } else {
+                sources.push(Source::new_remote(download_input)?);
+            }
+        }
+
+        let total = sources.len();
+        let mut failed = 0;
+        for (i, mut source) in sources.into_iter().enumerate() {
+            let remote_src = source.get_remote_source_mut()?;
+            let result = Self::download_source(&mut remote_src_proc, &download_config, remote_src);
+
+            match result {
+                Ok(DownloadedFile {
+                    path,
+                    tag_error: None,
+                }) => println!("[{}/{total}] {}", i + 1, path.display()),
+                Ok(DownloadedFile {
+                    path,
+                    tag_error: Some(err),
+                }) => println!(
+                    "[{}/{total}] {} (untagged: {:?})",
+                    i + 1,
+                    path.display(),
+                    err
+                ),
+                Err(err) => {
+                    failed += 1;
+                    println!("[{}/{total}] {}: {:?}", i + 1, remote_src.url, err);
+                }
+            }
+        }
+
+        if failed > 0 {
+            return Err(Error::OtherError(format!(
+                "{failed} of {total} downloads failed"
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn download_source(
+        remote_src_proc: &mut RemoteSourceProcessor,
+        download_config: &DownloadConfig,
+        remote_src: &mut Remote,
+    ) -> Result<DownloadedFile, Error> {
+        let mut result = remote_src_proc.set_audio_url_title(remote_src);
+        while let Err(err) = result {
+            match err {
+                Error::VideoBlockedInAllRegions | Error::VideoBlockedOnCopyRightGrounds => {
+                    return Err(err)
+                }
+                _ => {
+                    remote_src_proc.next_piped_api_domains_index()?;
+                    result = remote_src_proc.set_audio_url_title(remote_src);
+                }
+            }
+        }
+
+        download::download(download_config, remote_src)
+    }
+
     pub fn rank_piped_api_domains(config: &mut MusicPlayerConfig) -> Result<(), Error> {
         println!("Ranking Piped API domains: ");
         log::info!("MusicPlayerOptions::rank_piped_api_domains");
@@ -482,3 +906,79 @@ pub fn lev(a: &str, b: &str) -> u8 {
 
     v0.last().unwrap().to_owned().try_into().unwrap()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn extract_search_query_from_flag() {
+        let (rest, query) = MusicPlayerOptions::extract_search_query(args(&[
+            "rustunes",
+            "--search",
+            "./not a path",
+            "--shuffle_playlist=false",
+        ]))
+        .unwrap();
+
+        assert_eq!(rest, args(&["rustunes", "--shuffle_playlist=false"]));
+        assert_eq!(query.as_deref(), Some("./not a path"));
+    }
+
+    #[test]
+    fn extract_search_query_from_positional() {
+        let (rest, query) =
+            MusicPlayerOptions::extract_search_query(args(&["rustunes", "--debug_log", "lofi"]))
+                .unwrap();
+
+        assert_eq!(rest, args(&["rustunes", "--debug_log"]));
+        assert_eq!(query.as_deref(), Some("lofi"));
+    }
+
+    #[test]
+    fn extract_search_query_without_query() {
+        let (rest, query) =
+            MusicPlayerOptions::extract_search_query(args(&["rustunes", "--resume"])).unwrap();
+        assert_eq!(rest, args(&["rustunes", "--resume"]));
+        assert!(query.is_none());
+
+        let (_, query) = MusicPlayerOptions::extract_search_query(args(&["rustunes"])).unwrap();
+        assert!(query.is_none());
+    }
+
+    #[test]
+    fn extract_search_query_rejects_missing_paths() {
+        for path in [
+            "/missing/music",
+            "~/Music/missing",
+            "./missing",
+            "missing.flac",
+            "missing.m3u8",
+        ] {
+            let result = MusicPlayerOptions::extract_search_query(args(&["rustunes", path]));
+            assert!(
+                matches!(result, Err(Error::InvalidLocalPath(_))),
+                "{path}: {result:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn extract_search_query_rejects_urls() {
+        for url in [
+            "https://www.youtube.com/watch?x=dQw4w9WgXcQ",
+            "http://example.com/song",
+            "ftp://example.com/song.mp3",
+        ] {
+            let result = MusicPlayerOptions::extract_search_query(args(&["rustunes", url]));
+            assert!(
+                matches!(result, Err(Error::InvalidVideoUrl(_))),
+                "{url}: {result:?}"
+            );
+        }
+    }
+}
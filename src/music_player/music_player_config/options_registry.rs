@@ -1,3 +1,5 @@
+use crate::music_player::libmpv_handlers::SeekTarget;
+use crate::music_player::music_player_core::RepeatMode;
 use crate::music_player::tui::commands::commands_registry::Arg;
 use crate::music_player::tui::TuiState;
 use action_to_type_macro::ActionToType;
@@ -64,6 +66,9 @@ impl OptionsRegistry {
                 Arg::I64(_) => Arg::I64(Some(arg.parse().ok()?)),
                 Arg::U64(_) => Arg::U64(Some(arg.parse().ok()?)),
                 Arg::USIZE(_) => Arg::USIZE(Some(arg.parse().ok()?)),
+                Arg::SEEKTARGET(_) => Arg::SEEKTARGET(Some(SeekTarget::parse(arg)?)),
+                Arg::STRING(_) => Arg::STRING(Some(arg.to_string())),
+                Arg::REPEATMODE(_) => Arg::REPEATMODE(Some(RepeatMode::parse(arg)?)),
             };
             processed_args.push(processed_arg);
         }
@@ -127,7 +132,24 @@ pub enum OptionAction {
     SetMpvBaseVolume(i64),
     SetDebugLog(bool),
     SetPlayOnlyRecommendations(bool),
+    SetRelatedMixRatio(usize),
     SetVideoDurationLimit(u64),
+    SetVideoDurationMin(u64),
+    SetGenreCheck(bool),
+    SetAudioCodec(String),
+    SetAudioMaxBitrate(u64),
+    SetAudioLanguage(String),
+    SetAudioCacheDir(String),
+    SetDownloadDir(String),
+    SetPrefetchCount(usize),
+    SetExportM3U8OnQuit(bool),
+    SetExportJSONOnQuit(bool),
+    SetExportDir(String),
+    SetHistoryDedup(bool),
+    SetSearchPick(bool),
+    SetRepeat(RepeatMode),
+    Resume,
+    Offline,
     RankPipedApiDomains,
     RankInvidiousApiDomains,
     FetchPipedApiDomains,
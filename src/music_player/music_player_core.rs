@@ -1,22 +1,98 @@
+pub mod feedback;
+pub mod history;
 pub mod music_source;
+pub mod prefetch;
+pub mod session;
 
-use crate::music_player::libmpv_handlers::LibMpvSignals;
+use crate::music_player::libmpv_handlers::{LibMpvSignals, SeekTarget};
 use crate::music_player::music_player_config::MusicPlayerConfig;
 use crate::music_player::music_player_os_interface::OSInterfaceSignals;
 use crate::music_player::tui::{user_input_handler::TuiInputHandlerSignals, TuiSignals};
 use crate::music_player::Error;
 use rand::seq::SliceRandom;
 use rand::thread_rng;
-use std::collections::VecDeque;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+
+const SESSION_SAVE_INTERVAL_S: u64 = 30;
 
 #[derive(Debug)]
 pub enum MusicPlayerLogicSignals {
     PrepareNextFile,
-    PlaybackEnded,
+    PlaybackEnded(libmpv::EndFileReason),
+    PlaybackPause,
+    PlaybackResume,
+    PlaybackTime(f64),
+    UpdateVolume(i64),
     PlayPrev,
     End,
     BrokenUrl,
     RequestCurrentVideoURL,
+    RequestLyrics,
+    LyricsFetched(String, Result<music_source::lyrics::Lyrics, Error>),
+    ExportQueue(music_source::playlist_file::ExportFormat, Option<String>),
+    LoadHistory(usize),
+    QueueSelect(i16),
+    QueueJump,
+    QueueRemove,
+    QueueMoveUp,
+    QueueMoveDown,
+    QueueClearUpcoming,
+    AddToQueue(String),
+    AddNextToQueue(String),
+    AddPlaylistToQueue(String),
+    Search(String),
+    SearchSelect(i16),
+    SearchPlay,
+    SearchEnqueue,
+    SearchEnqueueNext,
+    SetRepeat(RepeatMode),
+    CycleRepeat,
+    ToggleShuffle,
+    Like,
+    Dislike,
+    DownloadCurrent,
+    Prefetched(String, prefetch::PrefetchResult),
+    PrefetchedRelated(String, usize, Result<music_source::Source, Error>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RepeatMode {
+    Off,
+    One,
+    All,
+    Related,
+}
+
+impl RepeatMode {
+    pub fn parse(mode: &str) -> Option<Self> {
+        match mode {
+            "off" => Some(RepeatMode::Off),
+            "one" => Some(RepeatMode::One),
+            "all" => Some(RepeatMode::All),
+            "related" => Some(RepeatMode::Related),
+            _ => None,
+        }
+    }
+
+    pub fn to_str(self) -> &'static str {
+        match self {
+            RepeatMode::Off => "off",
+            RepeatMode::One => "one",
+            RepeatMode::All => "all",
+            RepeatMode::Related => "related",
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            RepeatMode::Related => RepeatMode::Off,
+            RepeatMode::Off => RepeatMode::One,
+            RepeatMode::One => RepeatMode::All,
+            RepeatMode::All => RepeatMode::Related,
+        }
+    }
 }
 
 struct SignalSendersCollection {
@@ -35,12 +111,73 @@ pub struct MusicPlayerLogic {
     related_queue: VecDeque<String>,
     remote_src_proc: music_source::RemoteSourceProcessor,
     mp_logic_signal_recv: Option<crossbeam::channel::Receiver<MusicPlayerLogicSignals>>,
+    mp_logic_signal_send: Option<crossbeam::channel::Sender<MusicPlayerLogicSignals>>,
     signals_senders: SignalSendersCollection,
     play_only_recommendations: bool,
+    export_m3u8_on_quit: bool,
+    export_json_on_quit: bool,
+    export_dir: String,
+    history_db: history::HistoryDatabase,
+    session_path: String,
+    session: Option<session::SessionState>,
+    resume_position: Option<f64>,
+    playback_position: f64,
+    session_saved_at: std::time::Instant,
+    volume: i64,
+    libmpv_playlist_len: usize,
+    queue_cursor: usize,
+    idle: bool,
+    search_results: Vec<music_source::SearchResult>,
+    search_cursor: usize,
+    repeat: RepeatMode,
+    original_order: Vec<String>,
+    recommendation: music_source::recommendation::RecommendationConfig,
+    listened_ratios: HashMap<String, f64>,
+    feedback: feedback::FeedbackStore,
+    related_mix_ratio: usize,
+    mixed_related_ids: HashSet<String>,
+    skip_segments: Vec<music_source::sponsorblock::Segment>,
+    lyrics_api: String,
+    lyrics_requested: bool,
+    lyrics_title: String,
+    audio_cache: music_source::audio_cache::AudioCache,
+    offline: bool,
+    download_config: music_source::download::DownloadConfig,
+    prefetcher: prefetch::Prefetcher,
+    related_ahead: Option<music_source::Source>,
+    related_ahead_attempted: bool,
+    related_ahead_generation: usize,
 }
 
 impl MusicPlayerLogic {
     pub fn new(config: MusicPlayerConfig) -> Result<Self, Error> {
+        let history_db = history::HistoryDatabase::new(&config.history_db_path);
+
+        let mut remote_src_proc = music_source::RemoteSourceProcessor::new(
+            config.piped_api_domains,
+            config.piped_api_domain_index,
+            config.invidious_api_domains,
+            config.invidious_api_domain_index,
+            config.video_duration_limit_s,
+        )?;
+        let feedback = feedback::FeedbackStore::new(&config.feedback_path);
+        remote_src_proc.set_disliked_video_ids(feedback.get_disliked().clone());
+        if config.history_dedup {
+            remote_src_proc.set_excluded_video_ids(history_db.load_video_ids()?);
+        }
+        remote_src_proc.set_duration_min(config.video_duration_min_s);
+        remote_src_proc.set_sponsorblock(config.sponsorblock_api, config.sponsorblock_categories);
+        remote_src_proc.set_audio_preferences(music_source::audio_stream::AudioStreamPreferences {
+            codec: config.audio_codec,
+            max_bitrate_kbps: config.audio_max_bitrate_kbps,
+            language: config.audio_language,
+        });
+        remote_src_proc.set_genre_filter(config.accepted_genres, config.genre_check);
+        remote_src_proc.set_recommendation_config(config.recommendation.clone());
+        remote_src_proc.set_autoplay_filter(music_source::autoplay_filter::AutoplayFilter::new(
+            &config.autoplay_filters,
+        )?);
+
         Ok(MusicPlayerLogic {
             to_play: Vec::new(),
             to_play_index: 0,
@@ -48,21 +185,56 @@ impl MusicPlayerLogic {
             shuffle_playlist: config.shuffle_playlist,
             played_video_ids: Vec::new(),
             related_queue: VecDeque::new(),
-            remote_src_proc: music_source::RemoteSourceProcessor::new(
-                config.piped_api_domains,
-                config.piped_api_domain_index,
-                config.invidious_api_domains,
-                config.invidious_api_domain_index,
-                config.video_duration_limit_s,
-            )?,
+            remote_src_proc,
             mp_logic_signal_recv: None,
+            mp_logic_signal_send: None,
             signals_senders: SignalSendersCollection {
                 libmpv: None,
                 tui: None,
                 os_interface: None,
                 tui_input_handler: None,
             },
-            play_only_recommendations: config.play_only_recommendations,
+            play_only_recommendations: config.play_only_recommendations && !config.offline,
+            export_m3u8_on_quit: config.export_m3u8_on_quit,
+            export_json_on_quit: config.export_json_on_quit,
+            export_dir: config.export_dir.clone(),
+            history_db,
+            session_path: config.session_path,
+            session: None,
+            resume_position: None,
+            playback_position: 0.0,
+            session_saved_at: std::time::Instant::now(),
+            volume: config.mpv_base_volume,
+            libmpv_playlist_len: 0,
+            queue_cursor: 0,
+            idle: false,
+            search_results: Vec::new(),
+            search_cursor: 0,
+            repeat: config.repeat,
+            original_order: Vec::new(),
+            recommendation: config.recommendation,
+            listened_ratios: HashMap::new(),
+            feedback,
+            related_mix_ratio: config.related_mix_ratio,
+            mixed_related_ids: HashSet::new(),
+            skip_segments: Vec::new(),
+            lyrics_api: config.lyrics_api,
+            lyrics_requested: false,
+            lyrics_title: "".to_string(),
+            audio_cache: music_source::audio_cache::AudioCache::new(
+                &config.audio_cache_dir,
+                config.audio_cache_max_mb,
+            ),
+            offline: config.offline,
+            download_config: music_source::download::DownloadConfig {
+                dir: config.download_dir,
+                filename_template: config.download_filename_template,
+                ffmpeg_path: config.ffmpeg_path,
+            },
+            prefetcher: prefetch::Prefetcher::new(config.prefetch_count, config.prefetch_workers),
+            related_ahead: None,
+            related_ahead_attempted: false,
+            related_ahead_generation: 0,
         })
     }
 
@@ -70,6 +242,9 @@ impl MusicPlayerLogic {
         let (s, r) = crossbeam::channel::unbounded();
 
         self.mp_logic_signal_recv = Some(r);
+        self.mp_logic_signal_send = Some(s.clone());
+        self.prefetcher.start(s.clone());
+        self.audio_cache.start();
 
         s
     }
@@ -87,7 +262,54 @@ impl MusicPlayerLogic {
         self.signals_senders.tui_input_handler = Some(tui_input_handler_send);
     }
 
+    pub fn set_session(&mut self, session: session::SessionState) {
+        self.session = Some(session);
+    }
+
+    pub fn restore_session(&mut self) -> Result<(), Error> {
+        let session = match self.session.take() {
+            Some(session) => session,
+            None => {
+                return Err(Error::InvalidSessionFile(
+                    "No session to resume".to_string(),
+                ))
+            }
+        };
+
+        self.to_play = session.to_source_vec();
+        self.to_play_index = 0;
+        self.related_queue = session.related_queue;
+        self.played_video_ids = session.played_video_ids;
+        self.volume = session.volume;
+        if session.position_s > 0.0 {
+            self.resume_position = Some(session.position_s);
+        }
+
+        log::info!(
+            "MusicPlayerLogic::restore_session -> {:?}::{:?}",
+            self.to_play.len(),
+            self.resume_position
+        );
+
+        if self.to_play.is_empty() && self.related_queue.is_empty() {
+            return Err(Error::InvalidSessionFile(
+                "The saved session has nothing left to play".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
     pub fn validate_user_input(&mut self, user_input: &str) -> Result<(), Error> {
+        if music_source::Local::is_valid_path(user_input)
+            || music_source::Local::is_valid_dir_path(user_input)
+            || music_source::playlist_file::is_valid_playlist_file_path(user_input)
+        {
+            return Ok(());
+        }
+        if self.offline {
+            return self.validate_cached_input(user_input);
+        }
         if user_input.contains("list=") {
             let mut is_valid = self.remote_src_proc.is_valid_playlist_url(user_input);
             while is_valid.is_err() {
@@ -100,22 +322,51 @@ impl MusicPlayerLogic {
                 )));
             }
         } else {
-            let mut is_valid = self.remote_src_proc.is_valid_video_url(user_input);
-            while is_valid.is_err() {
-                self.handle_piped_api_domain_update()?;
-                is_valid = self.remote_src_proc.is_valid_video_url(user_input);
-            }
-            if !is_valid? {
-                return Err(Error::InvalidVideoUrl(format!(
-                    "The provided video URL is invalid: {user_input}"
-                )));
-            }
+            self.validate_video_url(user_input)?;
         }
         Ok(())
     }
 
-    pub fn process_user_input(&mut self, user_input: &str) -> Result<(), Error> {
+    fn validate_cached_input(&self, user_input: &str) -> Result<(), Error> {
         if user_input.contains("list=") {
+            return Err(Error::InvalidPlaylistUrl(format!(
+                "Playlists can't be played in offline mode: {user_input}"
+            )));
+        }
+
+        let video_id = music_source::Remote::url_into_video_id(user_input)?;
+        if !self.audio_cache.contains(&video_id) {
+            return Err(Error::InvalidVideoUrl(format!(
+                "The provided video is not cached: {user_input}"
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn validate_video_url(&mut self, user_input: &str) -> Result<(), Error> {
+        let mut is_valid = self.remote_src_proc.is_valid_video_url(user_input);
+        while is_valid.is_err() {
+            self.handle_piped_api_domain_update()?;
+            is_valid = self.remote_src_proc.is_valid_video_url(user_input);
+        }
+        if !is_valid? {
+            return Err(Error::InvalidVideoUrl(format!(
+                "The provided video URL is invalid: {user_input}"
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn process_user_input(&mut self, user_input: &str) -> Result<(), Error> {
+        if music_source::Local::is_valid_path(user_input) {
+            let music_source = music_source::Source::new_local(user_input)?;
+            self.to_play.push(music_source);
+        } else if music_source::Local::is_valid_dir_path(user_input) {
+            self.prepare_local_dir(user_input)?;
+        } else if music_source::playlist_file::is_valid_playlist_file_path(user_input) {
+            self.prepare_playlist_file(user_input)?;
+        } else if user_input.contains("list=") {
             self.playlist_to_play = music_source::Remote::url_into_playlist_id(user_input).unwrap();
             self.prepare_playlist()?;
 
@@ -146,20 +397,73 @@ impl MusicPlayerLogic {
         Ok(())
     }
 
+    pub fn prepare_audio_cache(&mut self) -> Result<(), Error> {
+        self.to_play = self
+            .audio_cache
+            .cached_video_ids()
+            .iter()
+            .filter_map(|video_id| {
+                music_source::Source::new_remote(&format!(
+                    "https://www.youtube.com/watch?v={video_id}"
+                ))
+                .ok()
+            })
+            .collect();
+
+        log::info!(
+            "MusicPlayerLogic::prepare_audio_cache -> {:?}",
+            self.to_play.len()
+        );
+
+        if self.to_play.is_empty() {
+            return Err(Error::OtherError("The audio cache is empty".to_string()));
+        }
+
+        self.original_order = self.to_play.iter().map(|src| src.to_url()).collect();
+        if self.shuffle_playlist {
+            self.to_play.shuffle(&mut thread_rng());
+        }
+
+        Ok(())
+    }
+
     fn prepare_playlist(&mut self) -> Result<(), Error> {
-        let mut to_play = self
-            .remote_src_proc
-            .playlist_to_remote_vec(&self.playlist_to_play);
+        self.to_play = self.fetch_playlist(&self.playlist_to_play.to_string())?;
+
+        self.original_order = self.to_play.iter().map(|src| src.to_url()).collect();
+        if self.shuffle_playlist {
+            self.to_play.shuffle(&mut thread_rng());
+        }
 
-        while to_play.is_err() {
+        Ok(())
+    }
+
+    fn fetch_playlist(&mut self, playlist_id: &str) -> Result<Vec<music_source::Source>, Error> {
+        let mut playlist = self.remote_src_proc.playlist_to_remote_vec(playlist_id);
+
+        while playlist.is_err() {
             self.handle_piped_api_domain_update()?;
-            to_play = self
-                .remote_src_proc
-                .playlist_to_remote_vec(&self.playlist_to_play);
+            playlist = self.remote_src_proc.playlist_to_remote_vec(playlist_id);
+        }
+
+        playlist
+    }
+
+    fn prepare_local_dir(&mut self, path: &str) -> Result<(), Error> {
+        self.to_play = music_source::Local::dir_to_local_vec(path)?;
+
+        self.original_order = self.to_play.iter().map(|src| src.to_url()).collect();
+        if self.shuffle_playlist {
+            self.to_play.shuffle(&mut thread_rng());
         }
 
-        self.to_play = to_play.unwrap();
+        Ok(())
+    }
 
+    fn prepare_playlist_file(&mut self, path: &str) -> Result<(), Error> {
+        self.to_play = music_source::playlist_file::playlist_file_to_source_vec(path)?;
+
+        self.original_order = self.to_play.iter().map(|src| src.to_url()).collect();
         if self.shuffle_playlist {
             self.to_play.shuffle(&mut thread_rng());
         }
@@ -168,28 +472,77 @@ impl MusicPlayerLogic {
     }
 
     pub fn handle_playback_logic(&mut self) -> Result<(), Error> {
+        self.set_repeat(self.repeat);
         self.prepare_next_to_play()?;
         self.update_tui()?;
         loop {
             if let Some(recv) = &self.mp_logic_signal_recv {
                 if let Ok(signal) = recv.recv() {
                     log::info!("MusicPlayerLogic::handle_playback_logic -> {:?}", signal);
-                    let os_interface_signal_send =
-                        self.signals_senders.os_interface.as_ref().unwrap();
                     match signal {
                         MusicPlayerLogicSignals::PrepareNextFile => {
+                            if let Some(position) = self.resume_position.take() {
+                                let libmpv_signal_send =
+                                    self.signals_senders.libmpv.as_ref().unwrap();
+                                libmpv_signal_send
+                                    .send(LibMpvSignals::Seek(SeekTarget::Absolute(position)))
+                                    .unwrap();
+                            }
                             self.prepare_next_to_play()?;
                         }
-                        MusicPlayerLogicSignals::PlaybackEnded => {
-                            let music_source = self.to_play.get_mut(self.to_play_index).unwrap();
-                            let remote_src = music_source.get_remote_source_mut()?;
+                        MusicPlayerLogicSignals::PlaybackEnded(reason) => {
+                            self.finish_history_entry(reason);
+
+                            if self.repeat == RepeatMode::All
+                                && self.to_play_index == self.to_play.len()
+                                && self.libmpv_playlist_len == self.to_play.len()
+                            {
+                                self.to_play_index = 0;
+                            }
 
-                            if remote_src.audio_stream_url.is_empty() {
-                                self.prepare_next_to_play()?;
+                            if self.to_play_index < self.to_play.len() {
+                                if self.to_play_index >= self.libmpv_playlist_len {
+                                    self.prepare_next_to_play()?;
+                                }
+                                self.update_tui()?;
+                            } else {
+                                self.idle = true;
                             }
-                            self.update_tui()?;
+                        }
+                        MusicPlayerLogicSignals::PlaybackPause => {
+                            self.history_db.pause();
+                        }
+                        MusicPlayerLogicSignals::PlaybackResume => {
+                            self.history_db.resume();
+                        }
+                        MusicPlayerLogicSignals::PlaybackTime(time) => {
+                            self.record_repeat_one_loop(time);
+                            self.playback_position = time;
+                            self.skip_segment(time);
+                        }
+                        MusicPlayerLogicSignals::UpdateVolume(volume) => {
+                            self.volume = volume;
                         }
                         MusicPlayerLogicSignals::End => {
+                            self.finish_history_entry(libmpv::mpv_end_file_reason::Quit);
+                            if self.export_m3u8_on_quit {
+                                self.export_queue(
+                                    music_source::playlist_file::ExportFormat::M3u8,
+                                    None,
+                                );
+                            }
+                            if self.export_json_on_quit {
+                                self.export_queue(
+                                    music_source::playlist_file::ExportFormat::Json,
+                                    None,
+                                );
+                            }
+                            self.save_session();
+
+                            let libmpv_signal_send = self.signals_senders.libmpv.as_ref().unwrap();
+                            let os_interface_signal_send =
+                                self.signals_senders.os_interface.as_ref().unwrap();
+                            libmpv_signal_send.send(LibMpvSignals::End).unwrap();
                             os_interface_signal_send
                                 .send(OSInterfaceSignals::End)
                                 .unwrap();
@@ -201,24 +554,100 @@ impl MusicPlayerLogic {
                             }
                         }
                         MusicPlayerLogicSignals::BrokenUrl => {
+                            let music_source = self.to_play.get(self.to_play_index - 1).unwrap();
+                            if !matches!(music_source, music_source::Source::Remote(_)) {
+                                log::info!(
+                                    "MusicPlayerLogic::handle_playback_logic::BrokenUrl -> {:?}",
+                                    music_source.to_url()
+                                );
+                                continue;
+                            }
                             self.handle_piped_api_domain_update()?;
                             self.broken_url()?;
                         }
+                        MusicPlayerLogicSignals::RequestLyrics => {
+                            self.lyrics_requested = true;
+                            self.update_lyrics();
+                        }
+                        MusicPlayerLogicSignals::LyricsFetched(title, result) => {
+                            self.show_lyrics(&title, result);
+                        }
                         MusicPlayerLogicSignals::RequestCurrentVideoURL => {
-                            let music_source =
-                                self.to_play.get_mut(self.to_play_index - 1).unwrap();
-                            let remote_src = music_source.get_remote_source_mut()?;
+                            let music_source = self.to_play.get(self.to_play_index - 1).unwrap();
 
                             self.signals_senders
                                 .tui_input_handler
                                 .as_ref()
                                 .unwrap()
-                                .send(TuiInputHandlerSignals::ClipboardCopyVideoUrl(format!(
-                                    "https://www.youtube.com/watch?v={}",
-                                    remote_src.video_id
-                                )))
+                                .send(TuiInputHandlerSignals::ClipboardCopyVideoUrl(
+                                    music_source.to_url(),
+                                ))
                                 .unwrap();
                         }
+                        MusicPlayerLogicSignals::ExportQueue(format, path) => {
+                            self.export_queue(format, path);
+                        }
+                        MusicPlayerLogicSignals::LoadHistory(sessions_count) => {
+                            self.load_history(sessions_count);
+                        }
+                        MusicPlayerLogicSignals::AddToQueue(user_input) => {
+                            self.add_to_queue(&user_input, false, false);
+                        }
+                        MusicPlayerLogicSignals::AddNextToQueue(user_input) => {
+                            self.add_to_queue(&user_input, true, false);
+                        }
+                        MusicPlayerLogicSignals::AddPlaylistToQueue(user_input) => {
+                            self.add_to_queue(&user_input, false, true);
+                        }
+                        MusicPlayerLogicSignals::SetRepeat(repeat) => {
+                            self.set_repeat(repeat);
+                        }
+                        MusicPlayerLogicSignals::CycleRepeat => {
+                            self.set_repeat(self.repeat.next());
+                        }
+                        MusicPlayerLogicSignals::ToggleShuffle => {
+                            self.toggle_shuffle()?;
+                        }
+                        MusicPlayerLogicSignals::Like => {
+                            self.rate_current(true);
+                        }
+                        MusicPlayerLogicSignals::Dislike => {
+                            self.rate_current(false);
+                        }
+                        MusicPlayerLogicSignals::DownloadCurrent => {
+                            self.download_current();
+                        }
+                        MusicPlayerLogicSignals::Prefetched(video_id, result) => {
+                            self.apply_prefetched(&video_id, result);
+                        }
+                        MusicPlayerLogicSignals::PrefetchedRelated(
+                            video_id,
+                            generation,
+                            result,
+                        ) => {
+                            self.apply_prefetched_related(&video_id, generation, result);
+                        }
+                        MusicPlayerLogicSignals::Search(query) => {
+                            self.search(&query);
+                        }
+                        MusicPlayerLogicSignals::SearchSelect(_)
+                        | MusicPlayerLogicSignals::SearchPlay
+                        | MusicPlayerLogicSignals::SearchEnqueue
+                        | MusicPlayerLogicSignals::SearchEnqueueNext => {
+                            self.handle_search_signal(signal)?;
+                        }
+                        MusicPlayerLogicSignals::QueueSelect(_)
+                        | MusicPlayerLogicSignals::QueueJump
+                        | MusicPlayerLogicSignals::QueueRemove
+                        | MusicPlayerLogicSignals::QueueMoveUp
+                        | MusicPlayerLogicSignals::QueueMoveDown
+                        | MusicPlayerLogicSignals::QueueClearUpcoming => {
+                            self.handle_queue_signal(signal)?;
+                        }
+                    }
+                    self.prefetch();
+                    if self.session_saved_at.elapsed().as_secs() >= SESSION_SAVE_INTERVAL_S {
+                        self.save_session();
                     }
                 }
             }
@@ -226,10 +655,105 @@ impl MusicPlayerLogic {
         Ok(())
     }
 
+    fn export_queue(
+        &self,
+        format: music_source::playlist_file::ExportFormat,
+        path: Option<String>,
+    ) {
+        let tui_signal_send = self.signals_senders.tui.as_ref().unwrap();
+
+        let path = path.unwrap_or(self.export_dir.to_string());
+        let result = music_source::playlist_file::export_queue(
+            &self.to_play,
+            self.to_play_index,
+            &self.played_video_ids,
+            &format,
+            &path,
+        );
+        log::info!("MusicPlayerLogic::export_queue -> {:?}", result);
+
+        let status = match result {
+            Ok(path) => format!("Exported queue to: {path}"),
+            Err(err) => format!("Export failed: {:?}", err),
+        };
+        tui_signal_send
+            .send(TuiSignals::UpdateStatus(status))
+            .unwrap();
+    }
+
+    fn save_session(&mut self) {
+        self.session_saved_at = std::time::Instant::now();
+        if self.session_path.is_empty() {
+            return;
+        }
+
+        let position = self.resume_position.unwrap_or(self.playback_position);
+
+        let session = session::SessionState::new(
+            &self.to_play,
+            self.to_play_index,
+            &self.related_queue,
+            &self.played_video_ids,
+            position,
+            self.volume,
+        );
+        let result = session.save(&self.session_path);
+
+        log::info!("MusicPlayerLogic::save_session -> {:?}", result);
+    }
+
+    fn finish_history_entry(&mut self, reason: libmpv::EndFileReason) {
+        let result = match reason {
+            libmpv::mpv_end_file_reason::Error => {
+                self.history_db.discard_entry();
+                Ok(None)
+            }
+            libmpv::mpv_end_file_reason::Stop => self.history_db.finish_entry(true),
+            _ => self.history_db.finish_entry(false),
+        };
+
+        match result {
+            Ok(Some(entry)) if !entry.video_id.is_empty() && entry.duration != 0 => {
+                let listened_ratio = entry.listened_s as f64 / entry.duration as f64;
+                self.listened_ratios.insert(entry.video_id, listened_ratio);
+            }
+            Err(_) => log::info!("MusicPlayerLogic::finish_history_entry -> {:?}", result),
+            _ => (),
+        }
+    }
+
+    fn load_history(&self, sessions_count: usize) {
+        let tui_signal_send = self.signals_senders.tui.as_ref().unwrap();
+
+        let result = self.history_db.load_past_sessions(sessions_count);
+        log::info!(
+            "MusicPlayerLogic::load_history -> {:?}",
+            result.as_ref().map(|entries| entries.len())
+        );
+
+        match result {
+            Ok(entries) => {
+                let lines = history::HistoryDatabase::entries_into_lines(&entries);
+                tui_signal_send
+                    .send(TuiSignals::UpdatePastHistory(lines))
+                    .unwrap();
+            }
+            Err(err) => {
+                tui_signal_send
+                    .send(TuiSignals::UpdateStatus(format!(
+                        "Loading history failed: {:?}",
+                        err
+                    )))
+                    .unwrap();
+            }
+        }
+    }
+
     fn broken_url(&mut self) -> Result<(), Error> {
         self.to_play_index -= 1;
 
         self.fix_broken_url(self.to_play_index)?;
+        self.truncate_libmpv_playlist(self.to_play_index + 1);
 
         let libmpv_signal_send = self.signals_senders.libmpv.as_ref().unwrap();
         let music_source = self.to_play.get_mut(self.to_play_index).unwrap();
@@ -241,7 +765,7 @@ impl MusicPlayerLogic {
             ))
             .unwrap();
         libmpv_signal_send
-            .send(LibMpvSignals::RemoveBrokenItem(self.to_play_index))
+            .send(LibMpvSignals::RemoveItem(self.to_play_index))
             .unwrap();
 
         self.to_play_index += 1;
@@ -277,48 +801,191 @@ impl MusicPlayerLogic {
         let tui_signal_send = self.signals_senders.tui.as_ref().unwrap();
         let os_interface_signal_send = self.signals_senders.os_interface.as_ref().unwrap();
 
-        let music_source = self.to_play.get_mut(self.to_play_index).unwrap();
-        let remote_src = music_source.get_remote_source_mut()?;
-
-        log::info!(
-            "MusicPlayerLogic::update_tui_data -> {:?}::{:?}",
-            remote_src.video_id,
-            remote_src.title
-        );
+        let music_source = match self.to_play.get(self.to_play_index) {
+            Some(music_source) => music_source,
+            None => return Ok(()),
+        };
+
+        let title = match music_source {
+            music_source::Source::Remote(remote_src) => {
+                log::info!(
+                    "MusicPlayerLogic::update_tui_data -> {:?}::{:?}",
+                    remote_src.video_id,
+                    remote_src.title
+                );
+
+                format!(
+                    "\"{}\"\n{}/{}",
+                    remote_src.title,
+                    self.remote_src_proc.get_piped_api_domain(),
+                    remote_src.video_id
+                )
+            }
+            music_source => {
+                log::info!(
+                    "MusicPlayerLogic::update_tui_data -> {:?}::{:?}",
+                    music_source.to_url(),
+                    music_source.get_title()
+                );
+
+                format!("{}\n{}", music_source.get_title(), music_source.to_url())
+            }
+        };
 
-        let remote_src = music_source.get_remote_source_mut()?;
         tui_signal_send
-            .send(TuiSignals::UpdateTitle(format!(
-                "{}\n{}/{}",
-                remote_src.title.to_string(),
-                self.remote_src_proc.get_piped_api_domain(),
-                remote_src.video_id
-            )))
+            .send(TuiSignals::UpdateTitle(title))
             .unwrap();
         tui_signal_send
-            .send(TuiSignals::UpdateDuration(remote_src.length))
+            .send(TuiSignals::UpdateDuration(music_source.get_length()))
+            .unwrap();
+        let audio_format = match music_source {
+            music_source::Source::Remote(remote_src) => remote_src.audio_format.to_string(),
+            _ => "".to_string(),
+        };
+        self.skip_segments = match music_source {
+            music_source::Source::Remote(remote_src) => remote_src.skip_segments.clone(),
+            _ => Vec::new(),
+        };
+        tui_signal_send
+            .send(TuiSignals::UpdateAudioFormat(audio_format))
             .unwrap();
         os_interface_signal_send
             .send(OSInterfaceSignals::UpdateMetadataTitle(
-                remote_src.title.to_string(),
+                music_source.get_title().to_string(),
             ))
             .unwrap();
 
+        if let music_source::Source::Remote(remote_src) = music_source {
+            self.remote_src_proc.record_uploader(&remote_src.uploader);
+            self.audio_cache.store_in_background(remote_src);
+        }
+        self.history_db.start_entry(music_source);
+        self.idle = false;
+
         self.to_play_index += 1;
+        self.queue_cursor = self.to_play_index - 1;
+
+        self.update_tui_queue();
+        self.playback_position = 0.0;
+        self.update_lyrics();
 
         Ok(())
     }
 
+    fn update_lyrics(&mut self) {
+        if !self.lyrics_requested || self.lyrics_api.is_empty() {
+            return;
+        }
+
+        let music_source = match self.to_play.get(self.to_play_index.saturating_sub(1)) {
+            Some(music_source) => music_source,
+            None => return,
+        };
+        let title = music_source.get_title().to_string();
+        if title == self.lyrics_title {
+            return;
+        }
+        log::info!("MusicPlayerLogic::update_lyrics -> {:?}", title);
+
+        let length = music_source.get_length();
+        let lyrics_api = self.lyrics_api.to_string();
+        let mp_logic_signal_send = self.mp_logic_signal_send.clone().unwrap();
+        self.lyrics_title = title.to_string();
+        std::thread::spawn(move || {
+            let result = music_source::lyrics::fetch_lyrics(&lyrics_api, &title, length);
+            let _ =
+                mp_logic_signal_send.send(MusicPlayerLogicSignals::LyricsFetched(title, result));
+        });
+    }
+
+    fn show_lyrics(&self, title: &str, result: Result<music_source::lyrics::Lyrics, Error>) {
+        log::info!(
+            "MusicPlayerLogic::show_lyrics -> {:?}::{:?}",
+            title,
+            result.as_ref().map(|lyrics| lyrics.is_empty())
+        );
+        if title != self.lyrics_title {
+            return;
+        }
+
+        let tui_signal_send = self.signals_senders.tui.as_ref().unwrap();
+        let lyrics = match result {
+            Ok(lyrics) => lyrics,
+            Err(err) => {
+                tui_signal_send
+                    .send(TuiSignals::UpdateStatus(format!("Lyrics: {:?}", err)))
+                    .unwrap();
+                music_source::lyrics::Lyrics::default()
+            }
+        };
+        tui_signal_send
+            .send(TuiSignals::UpdateLyrics(lyrics))
+            .unwrap();
+    }
+
     fn prepare_next_to_play(&mut self) -> Result<(), Error> {
         log::info!("MusicPlayerLogic::prepare_next_to_play",);
 
+        if self.should_mix_related() {
+            self.mix_related();
+        }
+
         let find_related_source = self.to_play_index == self.to_play.len();
         if find_related_source {
-            let related_source = self.find_related_source()?;
+            let autoplay_related =
+                self.repeat == RepeatMode::Related || self.play_only_recommendations;
+            if !autoplay_related || self.offline || self.related_queue.is_empty() {
+                log::info!("MusicPlayerLogic::prepare_next_to_play -> Nothing to play");
+                return Ok(());
+            }
+            let related_source = match self.related_ahead.take() {
+                Some(related_source) => related_source,
+                None => self.find_related_source(&[])?,
+            };
+            self.clear_related_ahead();
+            self.record_original_order(self.to_play.len(), std::slice::from_ref(&related_source));
             self.to_play.push(related_source);
         }
 
         let next_to_play_src = self.to_play.get_mut(self.to_play_index).unwrap();
+        if let music_source::Source::Local(next_to_play) = next_to_play_src {
+            log::info!(
+                "MusicPlayerLogic::prepare_next_to_play -> {:?}",
+                next_to_play.path
+            );
+
+            if self.to_play_index >= self.libmpv_playlist_len {
+                if next_to_play.title.is_empty() {
+                    next_to_play.set_title_length()?;
+                }
+
+                let libmpv_signal_send = self.signals_senders.libmpv.as_ref().unwrap();
+                libmpv_signal_send
+                    .send(LibMpvSignals::AddAudio(next_to_play.path.to_owned()))
+                    .unwrap();
+                self.libmpv_playlist_len += 1;
+            }
+            self.update_tui_queue();
+
+            return Ok(());
+        }
+        if let music_source::Source::Stream(next_to_play) = next_to_play_src {
+            log::info!(
+                "MusicPlayerLogic::prepare_next_to_play -> {:?}",
+                next_to_play.url
+            );
+
+            if self.to_play_index >= self.libmpv_playlist_len {
+                let libmpv_signal_send = self.signals_senders.libmpv.as_ref().unwrap();
+                libmpv_signal_send
+                    .send(LibMpvSignals::AddAudio(next_to_play.url.to_owned()))
+                    .unwrap();
+                self.libmpv_playlist_len += 1;
+            }
+            self.update_tui_queue();
+
+            return Ok(());
+        }
         let next_to_play = next_to_play_src.get_remote_source_mut().unwrap();
 
         log::info!(
@@ -335,6 +1002,33 @@ impl MusicPlayerLogic {
             self.related_queue.push_back(next_to_play.video_id.clone());
         }
 
+        if self.prefetcher.is_blocked(&next_to_play.video_id) {
+            self.to_play.remove(self.to_play_index);
+            return self.prepare_next_to_play();
+        }
+        let expired = next_to_play.is_stream_url_expired();
+        if expired {
+            log::info!(
+                "MusicPlayerLogic::prepare_next_to_play -> Expired: {:?}",
+                next_to_play.video_id
+            );
+            next_to_play.audio_stream_url.clear();
+        }
+        if next_to_play.audio_stream_url.is_empty() && self.audio_cache.apply_to(next_to_play) {
+            log::info!(
+                "MusicPlayerLogic::prepare_next_to_play -> {:?}",
+                next_to_play.audio_stream_url
+            );
+        }
+        if next_to_play.audio_stream_url.is_empty() && self.offline {
+            log::info!(
+                "MusicPlayerLogic::prepare_next_to_play -> Not cached: {:?}",
+                next_to_play.video_id
+            );
+            self.to_play.remove(self.to_play_index);
+            return self.prepare_next_to_play();
+        }
+
         if next_to_play.audio_stream_url.is_empty() {
             let result = Self::prepare_source(
                 next_to_play_src,
@@ -349,21 +1043,776 @@ impl MusicPlayerLogic {
                 }
                 _ => result?,
             }
-            let next_to_play = next_to_play_src.get_remote_source_mut().unwrap();
-            let libmpv_signal_send = self.signals_senders.libmpv.as_ref().unwrap();
+        }
+
+        let next_to_play = next_to_play_src.get_remote_source_mut().unwrap();
+        let libmpv_signal_send = self.signals_senders.libmpv.as_ref().unwrap();
+        if self.to_play_index >= self.libmpv_playlist_len {
             libmpv_signal_send
                 .send(LibMpvSignals::AddAudio(
                     next_to_play.audio_stream_url.to_owned(),
                 ))
                 .unwrap();
+            self.libmpv_playlist_len += 1;
+        } else if expired {
+            libmpv_signal_send
+                .send(LibMpvSignals::ReplaceItem(
+                    self.to_play_index,
+                    next_to_play.audio_stream_url.to_owned(),
+                ))
+                .unwrap();
+        }
+        self.update_tui_queue();
+
+        Ok(())
+    }
+
+    fn prefetch(&mut self) {
+        if !self.prefetcher.is_enabled() || self.offline {
+            return;
+        }
+
+        if self.prefetcher.get_depth() > 1 {
+            self.prefetch_related();
+        }
+
+        let start = self.to_play_index.max(self.libmpv_playlist_len);
+        let end = (self.to_play_index + self.prefetcher.get_depth()).min(self.to_play.len());
+        let upcoming = self.to_play.get(start..end).unwrap_or_default();
+
+        let mut remote_src_proc = None;
+        for music_source in upcoming.iter().chain(self.related_ahead.as_ref()) {
+            let remote_src = match music_source {
+                music_source::Source::Remote(remote_src) => remote_src,
+                _ => continue,
+            };
+
+            let unresolved =
+                remote_src.audio_stream_url.is_empty() || remote_src.is_stream_url_expired();
+            if unresolved && !self.audio_cache.contains(&remote_src.video_id) {
+                let remote_src_proc = remote_src_proc
+                    .get_or_insert_with(|| Arc::new(self.remote_src_proc.stream_resolver()));
+                self.prefetcher.request(remote_src, remote_src_proc);
+            }
+        }
+    }
+
+    fn prefetch_related(&mut self) {
+        let autoplay_related = self.repeat == RepeatMode::Related || self.play_only_recommendations;
+        if !autoplay_related
+            || self.related_ahead_attempted
+            || self.related_queue.is_empty()
+            || self.to_play_index + 1 != self.to_play.len()
+            || self.libmpv_playlist_len != self.to_play.len()
+        {
+            return;
+        }
+
+        self.related_ahead_attempted = true;
+        match self.pick_related_seed(&[]) {
+            Ok(related_video_id) => self.prefetcher.request_related(
+                &related_video_id,
+                &self.played_video_ids,
+                self.related_ahead_generation,
+                self.remote_src_proc.clone(),
+            ),
+            Err(err) => log::info!("MusicPlayerLogic::prefetch_related -> {:?}", err),
         }
+    }
+
+    // Results requested before the queue changed are stale and get dropped by generation
+    fn clear_related_ahead(&mut self) {
+        self.related_ahead = None;
+        self.related_ahead_attempted = false;
+        self.related_ahead_generation += 1;
+    }
+
+    fn apply_prefetched_related(
+        &mut self,
+        related_video_id: &str,
+        generation: usize,
+        result: Result<music_source::Source, Error>,
+    ) {
+        log::info!(
+            "MusicPlayerLogic::apply_prefetched_related -> {:?}::{:?}::{:?}",
+            related_video_id,
+            generation == self.related_ahead_generation,
+            result
+        );
+
+        if generation != self.related_ahead_generation {
+            return;
+        }
+
+        match result {
+            Ok(related_source) => self.related_ahead = Some(related_source),
+            Err(Error::VideoBlockedInAllRegions)
+            | Err(Error::VideoBlockedOnCopyRightGrounds)
+            | Err(Error::NoRelatedVideoFound(_)) => {
+                self.drop_related_seed(related_video_id);
+                self.related_ahead_attempted = false;
+            }
+            Err(_) => (),
+        }
+    }
+
+    fn apply_prefetched(&mut self, video_id: &str, result: prefetch::PrefetchResult) {
+        self.prefetcher.finish(video_id, &result);
+
+        let prefetched = match result {
+            prefetch::PrefetchResult::Resolved(prefetched) => prefetched,
+            _ => return,
+        };
+
+        let start = self.to_play_index.max(self.libmpv_playlist_len);
+        let mut applied = false;
+        let upcoming = self.to_play.iter_mut().skip(start);
+        for music_source in upcoming.chain(self.related_ahead.as_mut()) {
+            if let music_source::Source::Remote(remote_src) = music_source {
+                let unresolved =
+                    remote_src.audio_stream_url.is_empty() || remote_src.is_stream_url_expired();
+                if remote_src.video_id == video_id && unresolved {
+                    *remote_src = music_source::Remote {
+                        url: remote_src.url.clone(),
+                        ..(*prefetched).clone()
+                    };
+                    applied = true;
+                }
+            }
+        }
+
+        log::info!(
+            "MusicPlayerLogic::apply_prefetched -> {:?}::{:?}",
+            video_id,
+            applied
+        );
+    }
+
+    fn add_to_queue(&mut self, user_input: &str, play_next: bool, playlist: bool) {
+        let result = self.add_to_queue_impl(user_input, play_next, playlist);
+        log::info!("MusicPlayerLogic::add_to_queue -> {:?}", result);
+
+        let tui_signal_send = self.signals_senders.tui.as_ref().unwrap();
+        let status = match result {
+            Ok(added) => format!("Added {added} item(s) to queue"),
+            Err(err) => format!("Adding to queue failed: {:?}", err),
+        };
+        tui_signal_send
+            .send(TuiSignals::UpdateStatus(status))
+            .unwrap();
+    }
+
+    fn add_to_queue_impl(
+        &mut self,
+        user_input: &str,
+        play_next: bool,
+        playlist: bool,
+    ) -> Result<usize, Error> {
+        let mut sources = if music_source::Local::is_valid_path(user_input) {
+            vec![music_source::Source::new_local(user_input)?]
+        } else if music_source::Local::is_valid_dir_path(user_input) {
+            music_source::Local::dir_to_local_vec(user_input)?
+        } else if music_source::playlist_file::is_valid_playlist_file_path(user_input) {
+            music_source::playlist_file::playlist_file_to_source_vec(user_input)?
+        } else if playlist {
+            let playlist_id = music_source::Remote::url_into_playlist_id(user_input)?;
+            self.validate_user_input(user_input)?;
+            self.fetch_playlist(&playlist_id)?
+        } else {
+            self.validate_video_url(user_input)?;
+            vec![music_source::Source::new_remote(user_input)?]
+        };
+
+        if sources.len() > 1 && self.shuffle_playlist {
+            sources.shuffle(&mut thread_rng());
+        }
+
+        self.insert_into_queue(sources, play_next)
+    }
+
+    fn insert_into_queue(
+        &mut self,
+        sources: Vec<music_source::Source>,
+        play_next: bool,
+    ) -> Result<usize, Error> {
+        let added = sources.len();
+
+        if play_next {
+            self.truncate_libmpv_playlist(self.to_play_index);
+            let index = self.to_play_index.min(self.to_play.len());
+            self.record_original_order(index, &sources);
+            self.to_play.splice(index..index, sources);
+        } else {
+            self.record_original_order(self.to_play.len(), &sources);
+            self.to_play.extend(sources);
+        }
+        self.clear_related_ahead();
+
+        if self.idle {
+            self.prepare_next_to_play()?;
+            self.update_tui()?;
+        } else if self.to_play_index >= self.libmpv_playlist_len {
+            self.prepare_next_to_play()?;
+        }
+        self.update_tui_queue();
+
+        Ok(added)
+    }
+
+    fn set_repeat(&mut self, repeat: RepeatMode) {
+        log::info!("MusicPlayerLogic::set_repeat -> {:?}", repeat);
+        self.repeat = repeat;
+
+        let libmpv_signal_send = self.signals_senders.libmpv.as_ref().unwrap();
+        let tui_signal_send = self.signals_senders.tui.as_ref().unwrap();
+        libmpv_signal_send
+            .send(LibMpvSignals::SetRepeat(repeat))
+            .unwrap();
+        tui_signal_send
+            .send(TuiSignals::UpdateRepeat(repeat))
+            .unwrap();
+    }
+
+    // mpv repeats a file with loop-file without ending it, so a jump from its end
+    // back to the start is recorded as a new playback
+    fn record_repeat_one_loop(&mut self, time: f64) {
+        if self.repeat != RepeatMode::One || self.to_play_index == 0 {
+            return;
+        }
+
+        let length = self.to_play[self.to_play_index - 1].get_length() as f64;
+        if length == 0.0 || time >= 1.0 || self.playback_position + 2.0 < length {
+            return;
+        }
+        log::info!("MusicPlayerLogic::record_repeat_one_loop -> {:?}", time);
+
+        self.finish_history_entry(libmpv::mpv_end_file_reason::Eof);
+        self.history_db
+            .start_entry(&self.to_play[self.to_play_index - 1]);
+    }
+
+    fn skip_segment(&self, time: f64) {
+        let segment = match self
+            .skip_segments
+            .iter()
+            .find(|segment| segment.contains(time))
+        {
+            Some(segment) => segment,
+            None => return,
+        };
+        log::info!("MusicPlayerLogic::skip_segment -> {:?}", segment);
+
+        let libmpv_signal_send = self.signals_senders.libmpv.as_ref().unwrap();
+        let tui_signal_send = self.signals_senders.tui.as_ref().unwrap();
+        libmpv_signal_send
+            .send(LibMpvSignals::SkipSegment(segment.start, segment.end))
+            .unwrap();
+        tui_signal_send
+            .send(TuiSignals::UpdateStatus(format!(
+                "Skipped {} segment",
+                segment.category
+            )))
+            .unwrap();
+    }
+
+    fn download_current(&self) {
+        let tui_signal_send = self.signals_senders.tui.as_ref().unwrap().clone();
+
+        let remote_src = match self
+            .to_play
+            .get(self.to_play_index.saturating_sub(1))
+            .map(|music_source| music_source.get_remote_source())
+        {
+            Some(Ok(remote_src)) if !remote_src.audio_stream_url.is_empty() => remote_src.clone(),
+            _ => {
+                tui_signal_send
+                    .send(TuiSignals::UpdateStatus(
+                        "Download failed: Only remote tracks can be downloaded".to_string(),
+                    ))
+                    .unwrap();
+                return;
+            }
+        };
+        tui_signal_send
+            .send(TuiSignals::UpdateStatus(format!(
+                "Downloading: {}",
+                remote_src.title
+            )))
+            .unwrap();
+
+        let download_config = self.download_config.clone();
+        std::thread::spawn(move || {
+            let result = music_source::download::download(&download_config, &remote_src);
+            log::info!(
+                "MusicPlayerLogic::download_current -> {:?}::{:?}",
+                remote_src.video_id,
+                result
+            );
+
+            let status = match result {
+                Ok(music_source::download::DownloadedFile {
+                    path,
+                    tag_error: None,
+                }) => format!("Downloaded: {}", path.display()),
+                Ok(music_source::download::DownloadedFile {
+                    path,
+                    tag_error: Some(err),
+                }) => format!("Downloaded (untagged: {:?}): {}", err, path.display()),
+                Err(err) => format!("Download failed: {:?}", err),
+            };
+            let _ = tui_signal_send.send(TuiSignals::UpdateStatus(status));
+        });
+    }
+
+    fn rate_current(&mut self, like: bool) {
+        let result = self.rate_current_impl(like);
+        log::info!("MusicPlayerLogic::rate_current -> {:?}", result);
+
+        let status = match result {
+            Ok(title) if like => format!("Liked: {title}"),
+            Ok(title) => format!("Disliked: {title}"),
+            Err(err) => format!("Rating failed: {:?}", err),
+        };
+
+        let tui_signal_send = self.signals_senders.tui.as_ref().unwrap();
+        tui_signal_send
+            .send(TuiSignals::UpdateStatus(status))
+            .unwrap();
+    }
+
+    fn rate_current_impl(&mut self, like: bool) -> Result<String, Error> {
+        let music_source = self
+            .to_play
+            .get(self.to_play_index.saturating_sub(1))
+            .ok_or_else(|| Error::OtherError("Nothing is playing".to_string()))?;
+        let remote_src = music_source.get_remote_source()?;
+        let video_id = remote_src.video_id.clone();
+        let title = remote_src.title.clone();
+
+        if like {
+            if !self.related_queue.contains(&video_id) {
+                self.related_queue.push_back(video_id.clone());
+            }
+            self.remote_src_proc.set_video_disliked(&video_id, false);
+            self.feedback.like(&video_id)?;
+        } else {
+            self.drop_related_seed(&video_id);
+            self.remote_src_proc.set_video_disliked(&video_id, true);
+            self.feedback.dislike(&video_id)?;
+
+            let libmpv_signal_send = self.signals_senders.libmpv.as_ref().unwrap();
+            libmpv_signal_send.send(LibMpvSignals::PlayNext).unwrap();
+        }
+
+        Ok(title)
+    }
+
+    fn toggle_shuffle(&mut self) -> Result<(), Error> {
+        self.shuffle_playlist = !self.shuffle_playlist;
+        log::info!(
+            "MusicPlayerLogic::toggle_shuffle -> {:?}",
+            self.shuffle_playlist
+        );
+
+        let upcoming_index = self.to_play_index.saturating_sub(1) + 1;
+        if upcoming_index < self.to_play.len() {
+            self.truncate_libmpv_playlist(upcoming_index);
+
+            let mut upcoming = self.to_play.split_off(upcoming_index);
+            if self.shuffle_playlist {
+                upcoming.shuffle(&mut thread_rng());
+            } else {
+                upcoming = self.restore_original_order(upcoming);
+            }
+            self.to_play.extend(upcoming);
+            self.clear_related_ahead();
+
+            self.prepare_next_to_play()?;
+        }
+
+        let tui_signal_send = self.signals_senders.tui.as_ref().unwrap();
+        let status = if self.shuffle_playlist {
+            "Shuffle: on"
+        } else {
+            "Shuffle: off"
+        };
+        tui_signal_send
+            .send(TuiSignals::UpdateStatus(status.to_string()))
+            .unwrap();
+        self.update_tui_queue();
+
+        Ok(())
+    }
+
+    // Keeps the unshuffled order in step with tracks inserted at `index` of `to_play`
+    fn record_original_order(&mut self, index: usize, sources: &[music_source::Source]) {
+        let position = self
+            .to_play
+            .get(index)
+            .and_then(|music_source| {
+                let url = music_source.to_url();
+                self.original_order
+                    .iter()
+                    .position(|original_url| *original_url == url)
+            })
+            .unwrap_or(self.original_order.len());
+
+        self.original_order.splice(
+            position..position,
+            sources.iter().map(|music_source| music_source.to_url()),
+        );
+    }
+
+    fn restore_original_order(
+        &self,
+        upcoming: Vec<music_source::Source>,
+    ) -> Vec<music_source::Source> {
+        // Each entry of the original order is claimed once so that duplicates keep their places
+        let mut claimed = vec![false; self.original_order.len()];
+        let mut claim = |url: String| {
+            let position = self
+                .original_order
+                .iter()
+                .enumerate()
+                .position(|(i, original_url)| !claimed[i] && *original_url == url);
+            if let Some(position) = position {
+                claimed[position] = true;
+            }
+
+            position.unwrap_or(usize::MAX)
+        };
+
+        for music_source in &self.to_play {
+            claim(music_source.to_url());
+        }
+        let mut upcoming: Vec<(usize, music_source::Source)> = upcoming
+            .into_iter()
+            .map(|music_source| (claim(music_source.to_url()), music_source))
+            .collect();
+        upcoming.sort_by_key(|(position, _)| *position);
+
+        upcoming
+            .into_iter()
+            .map(|(_, music_source)| music_source)
+            .collect()
+    }
+
+    fn search(&mut self, query: &str) {
+        let tui_signal_send = self.signals_senders.tui.as_ref().unwrap();
+        tui_signal_send
+            .send(TuiSignals::UpdateStatus(format!("Searching: {query}")))
+            .unwrap();
+
+        let mut result = self.remote_src_proc.search(query);
+        while result.is_err() {
+            if let Err(err) = self.handle_piped_api_domain_update() {
+                result = Err(err);
+                break;
+            }
+            result = self.remote_src_proc.search(query);
+        }
+        log::info!(
+            "MusicPlayerLogic::search -> {:?}",
+            result.as_ref().map(|results| results.len())
+        );
+
+        let status = match result {
+            Ok(results) => {
+                let status = format!("Found {} result(s) for: {query}", results.len());
+                self.search_results = results;
+                self.search_cursor = 0;
+                status
+            }
+            Err(err) => format!("Search failed: {:?}", err),
+        };
+
+        let tui_signal_send = self.signals_senders.tui.as_ref().unwrap();
+        tui_signal_send
+            .send(TuiSignals::UpdateStatus(status))
+            .unwrap();
+        self.update_tui_search_results();
+    }
+
+    fn update_tui_search_results(&self) {
+        let tui_signal_send = self.signals_senders.tui.as_ref().unwrap();
+
+        let results = self
+            .search_results
+            .iter()
+            .map(|result| result.to_display_str())
+            .collect();
+
+        tui_signal_send
+            .send(TuiSignals::UpdateSearchResults(results, self.search_cursor))
+            .unwrap();
+    }
+
+    fn handle_search_signal(&mut self, signal: MusicPlayerLogicSignals) -> Result<(), Error> {
+        if let MusicPlayerLogicSignals::SearchSelect(offset) = signal {
+            let cursor = self.search_cursor as i64 + offset as i64;
+            let max_cursor = self.search_results.len().saturating_sub(1) as i64;
+            self.search_cursor = cursor.clamp(0, max_cursor) as usize;
+            self.update_tui_search_results();
+
+            return Ok(());
+        }
+
+        let search_result = match self.search_results.get(self.search_cursor) {
+            Some(search_result) => search_result.clone(),
+            None => return Ok(()),
+        };
+
+        let mut music_source = music_source::Source::new_remote(&search_result.url)?;
+        music_source.set_title_length(
+            Some(search_result.title.clone()),
+            Some(search_result.duration),
+        );
+
+        let play_next = !matches!(signal, MusicPlayerLogicSignals::SearchEnqueue);
+        let idle = self.idle;
+        self.insert_into_queue(vec![music_source], play_next)?;
+
+        if matches!(signal, MusicPlayerLogicSignals::SearchPlay) && !idle {
+            let libmpv_signal_send = self.signals_senders.libmpv.as_ref().unwrap();
+            libmpv_signal_send.send(LibMpvSignals::PlayNext).unwrap();
+        }
+
+        let tui_signal_send = self.signals_senders.tui.as_ref().unwrap();
+        tui_signal_send
+            .send(TuiSignals::UpdateStatus(format!(
+                "Queued: {}",
+                search_result.title
+            )))
+            .unwrap();
 
         Ok(())
     }
 
-    fn find_related_source(&mut self) -> Result<music_source::Source, Error> {
-        let mut related_video_id = self.related_queue.pop_front().unwrap();
-        self.related_queue.push_back(related_video_id.clone());
+    fn truncate_libmpv_playlist(&mut self, len: usize) {
+        if self.libmpv_playlist_len <= len {
+            return;
+        }
+
+        let libmpv_signal_send = self.signals_senders.libmpv.as_ref().unwrap();
+        libmpv_signal_send
+            .send(LibMpvSignals::TruncatePlaylist(len))
+            .unwrap();
+
+        self.libmpv_playlist_len = len;
+    }
+
+    fn update_tui_queue(&self) {
+        let tui_signal_send = self.signals_senders.tui.as_ref().unwrap();
+
+        let queue = self
+            .to_play
+            .iter()
+            .map(|music_source| {
+                let title = music_source.get_title();
+                if title.is_empty() {
+                    music_source.to_url()
+                } else {
+                    title.to_string()
+                }
+            })
+            .collect();
+
+        tui_signal_send
+            .send(TuiSignals::UpdateQueue(
+                queue,
+                self.to_play_index.saturating_sub(1),
+                self.queue_cursor,
+            ))
+            .unwrap();
+    }
+
+    fn handle_queue_signal(&mut self, signal: MusicPlayerLogicSignals) -> Result<(), Error> {
+        let current_index = self.to_play_index.saturating_sub(1);
+        let cursor = self.queue_cursor;
+
+        match signal {
+            MusicPlayerLogicSignals::QueueSelect(offset) => {
+                let cursor = cursor as i64 + offset as i64;
+                let max_cursor = self.to_play.len().saturating_sub(1) as i64;
+                self.queue_cursor = cursor.clamp(0, max_cursor) as usize;
+            }
+            MusicPlayerLogicSignals::QueueJump => {
+                if cursor >= self.to_play.len() || cursor == current_index {
+                    return Ok(());
+                }
+
+                if cursor < self.libmpv_playlist_len {
+                    self.to_play_index = cursor;
+                } else {
+                    self.truncate_libmpv_playlist(current_index + 1);
+                    let music_source = self.to_play.remove(cursor);
+                    self.to_play.insert(current_index + 1, music_source);
+                    self.to_play_index = current_index + 1;
+                    self.queue_cursor = self.to_play_index;
+                    self.prepare_next_to_play()?;
+                }
+
+                let libmpv_signal_send = self.signals_senders.libmpv.as_ref().unwrap();
+                libmpv_signal_send
+                    .send(LibMpvSignals::PlayIndex(self.to_play_index))
+                    .unwrap();
+            }
+            MusicPlayerLogicSignals::QueueRemove => {
+                if cursor >= self.to_play.len() || cursor == current_index {
+                    return Ok(());
+                }
+
+                if cursor < current_index {
+                    let libmpv_signal_send = self.signals_senders.libmpv.as_ref().unwrap();
+                    libmpv_signal_send
+                        .send(LibMpvSignals::RemoveItem(cursor))
+                        .unwrap();
+                    self.libmpv_playlist_len -= 1;
+                    self.to_play_index -= 1;
+                    self.to_play.remove(cursor);
+                } else {
+                    self.truncate_libmpv_playlist(current_index + 1);
+                    self.to_play.remove(cursor);
+                    self.prepare_next_to_play()?;
+                }
+
+                self.queue_cursor = cursor.min(self.to_play.len().saturating_sub(1));
+            }
+            MusicPlayerLogicSignals::QueueMoveUp => {
+                if cursor <= current_index + 1 || cursor >= self.to_play.len() {
+                    return Ok(());
+                }
+
+                self.truncate_libmpv_playlist(current_index + 1);
+                self.to_play.swap(cursor, cursor - 1);
+                self.queue_cursor = cursor - 1;
+                self.prepare_next_to_play()?;
+            }
+            MusicPlayerLogicSignals::QueueMoveDown => {
+                if cursor <= current_index || cursor + 1 >= self.to_play.len() {
+                    return Ok(());
+                }
+
+                self.truncate_libmpv_playlist(current_index + 1);
+                self.to_play.swap(cursor, cursor + 1);
+                self.queue_cursor = cursor + 1;
+                self.prepare_next_to_play()?;
+            }
+            MusicPlayerLogicSignals::QueueClearUpcoming => {
+                self.truncate_libmpv_playlist(current_index + 1);
+                self.to_play.truncate(current_index + 1);
+                self.queue_cursor = cursor.min(current_index);
+                self.prepare_next_to_play()?;
+            }
+            _ => (),
+        }
+
+        self.update_tui_queue();
+
+        Ok(())
+    }
+
+    fn is_mixed_related(&self, music_source: &music_source::Source) -> bool {
+        match music_source {
+            music_source::Source::Remote(remote_src) => {
+                self.mixed_related_ids.contains(&remote_src.video_id)
+            }
+            _ => false,
+        }
+    }
+
+    fn should_mix_related(&self) -> bool {
+        let ratio = self.related_mix_ratio;
+        if ratio == 0
+            || self.offline
+            || self.play_only_recommendations
+            || self.related_queue.is_empty()
+            || self.to_play_index < ratio
+            || self.to_play_index >= self.to_play.len()
+            || self.to_play_index < self.libmpv_playlist_len
+            || self.is_mixed_related(&self.to_play[self.to_play_index])
+        {
+            return false;
+        }
+
+        let played_since_related = self.to_play[..self.to_play_index]
+            .iter()
+            .rev()
+            .take_while(|music_source| !self.is_mixed_related(music_source))
+            .count();
+
+        played_since_related >= ratio
+    }
+
+    fn mix_related(&mut self) {
+        let seed_pool: Vec<String> = self.to_play
+            [self.to_play_index - self.related_mix_ratio..self.to_play_index]
+            .iter()
+            .filter_map(|music_source| music_source.get_remote_source().ok())
+            .map(|remote_src| remote_src.video_id.clone())
+            .collect();
+
+        match self.find_related_source(&seed_pool) {
+            Ok(related_source) => {
+                let remote_src = related_source.get_remote_source().unwrap();
+                log::info!("MusicPlayerLogic::mix_related -> {:?}", remote_src.video_id);
+
+                self.mixed_related_ids.insert(remote_src.video_id.clone());
+                self.record_original_order(
+                    self.to_play_index,
+                    std::slice::from_ref(&related_source),
+                );
+                self.to_play.insert(self.to_play_index, related_source);
+            }
+            Err(err) => log::info!("MusicPlayerLogic::mix_related -> {:?}", err),
+        }
+    }
+
+    fn pick_related_seed(&self, seed_pool: &[String]) -> Result<String, Error> {
+        let seed_pool_queued = self
+            .related_queue
+            .iter()
+            .any(|video_id| seed_pool.contains(video_id));
+
+        let played_count = self.played_video_ids.len();
+        let seeds: Vec<(String, f64)> = self
+            .related_queue
+            .iter()
+            .filter(|video_id| !seed_pool_queued || seed_pool.contains(video_id))
+            .map(|video_id| {
+                let recency_rank = self
+                    .played_video_ids
+                    .iter()
+                    .rev()
+                    .position(|played_id| played_id == video_id)
+                    .unwrap_or(played_count);
+                let listened_ratio = self.listened_ratios.get(video_id).copied().unwrap_or(1.0);
+                let mut weight = music_source::recommendation::seed_weight(
+                    recency_rank,
+                    listened_ratio,
+                    &self.recommendation,
+                );
+                if self.feedback.is_liked(video_id) {
+                    weight *= self.recommendation.liked_seed_boost;
+                }
+
+                (video_id.clone(), weight)
+            })
+            .collect();
+
+        music_source::recommendation::pick_seed(&seeds, &self.recommendation).ok_or_else(|| {
+            Error::NoRelatedVideoFound("No seeds left to find related videos".to_string())
+        })
+    }
+
+    fn drop_related_seed(&mut self, video_id: &str) {
+        log::info!("MusicPlayerLogic::drop_related_seed -> {:?}", video_id);
+
+        self.related_queue.retain(|seed| seed != video_id);
+    }
+
+    fn find_related_source(&mut self, seed_pool: &[String]) -> Result<music_source::Source, Error> {
+        let mut related_video_id = self.pick_related_seed(seed_pool)?;
 
         log::info!(
             "MusicPlayerLogic::find_related_source -> {:?}",
@@ -380,14 +1829,10 @@ impl MusicPlayerLogic {
         while related_source.is_err() {
             match related_source.unwrap_err() {
                 Error::VideoBlockedInAllRegions | Error::VideoBlockedOnCopyRightGrounds => {
-                    let poped = self.related_queue.pop_back();
-                    log::info!(
-                        "MusicPlayerLogic::find_related_source::Error::VideoBlocked -> {:?}",
-                        poped
-                    );
+                    log::info!("MusicPlayerLogic::find_related_source::Error::VideoBlocked");
+                    self.drop_related_seed(&related_video_id);
 
-                    related_video_id = self.related_queue.pop_front().unwrap();
-                    self.related_queue.push_back(related_video_id.clone());
+                    related_video_id = self.pick_related_seed(seed_pool)?;
                 }
                 Error::AllInvidiousApiDomainsDown(_) => {
                     log::info!(
@@ -403,14 +1848,12 @@ impl MusicPlayerLogic {
                     if !invidious_api_domains_error && !piped_api_domains_error {
                         piped_api_domains_error = self.handle_piped_api_domain_update()?;
                     } else {
-                        let poped = self.related_queue.pop_back();
                         log::info!(
-                            "MusicPlayerLogic::find_related_source::Error::NoRelatedVideoFound -> {:?}",
-                            poped
+                            "MusicPlayerLogic::find_related_source::Error::NoRelatedVideoFound"
                         );
+                        self.drop_related_seed(&related_video_id);
 
-                        related_video_id = self.related_queue.pop_front().unwrap();
-                        self.related_queue.push_back(related_video_id.clone());
+                        related_video_id = self.pick_related_seed(seed_pool)?;
                     }
                 }
                 _ => piped_api_domains_error = self.handle_piped_api_domain_update()?,
@@ -474,6 +1917,7 @@ impl MusicPlayerLogic {
                 }
             }
         }
+        remote_src_proc.set_skip_segments(music_src);
 
         Ok(())
     }
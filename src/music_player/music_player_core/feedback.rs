@@ -0,0 +1,126 @@
+use crate::music_player::Error;
+use std::collections::HashSet;
+
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct FeedbackState {
+    pub liked: HashSet<String>,
+    pub disliked: HashSet<String>,
+}
+
+pub struct FeedbackStore {
+    path: String,
+    state: FeedbackState,
+}
+
+impl FeedbackStore {
+    pub fn new(path: &str) -> Self {
+        let state = match std::fs::read_to_string(path) {
+            Ok(feedback) => serde_json::from_str(&feedback).unwrap_or_else(|err| {
+                log::info!("FeedbackStore::new -> {:?}", err);
+                FeedbackState::default()
+            }),
+            Err(_) => FeedbackState::default(),
+        };
+
+        log::info!(
+            "FeedbackStore::new -> {:?}::{:?}",
+            state.liked.len(),
+            state.disliked.len()
+        );
+
+        Self {
+            path: path.to_string(),
+            state,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.path.is_empty()
+    }
+
+    pub fn is_liked(&self, video_id: &str) -> bool {
+        self.state.liked.contains(video_id)
+    }
+
+    pub fn get_disliked(&self) -> &HashSet<String> {
+        &self.state.disliked
+    }
+
+    pub fn like(&mut self, video_id: &str) -> Result<(), Error> {
+        self.state.disliked.remove(video_id);
+        self.state.liked.insert(video_id.to_string());
+
+        self.save()
+    }
+
+    pub fn dislike(&mut self, video_id: &str) -> Result<(), Error> {
+        self.state.liked.remove(video_id);
+        self.state.disliked.insert(video_id.to_string());
+
+        self.save()
+    }
+
+    fn save(&self) -> Result<(), Error> {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+
+        let feedback = serde_json::to_string_pretty(&self.state)?;
+
+        let tmp_path = format!("{}.tmp", self.path);
+        std::fs::write(&tmp_path, feedback)?;
+        std::fs::rename(&tmp_path, &self.path)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> String {
+        let path = crate::utils::temp_path(&format!("feedback_{name}.json"));
+
+        path.to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn like_and_dislike_are_exclusive() {
+        let mut feedback = FeedbackStore::new("");
+
+        feedback.dislike("a").unwrap();
+        assert!(feedback.get_disliked().contains("a"));
+        assert!(!feedback.is_liked("a"));
+
+        feedback.like("a").unwrap();
+        assert!(feedback.is_liked("a"));
+        assert!(feedback.get_disliked().is_empty());
+    }
+
+    #[test]
+    fn feedback_is_persisted() {
+        let path = temp_path("persist");
+
+        let mut feedback = FeedbackStore::new(&path);
+        feedback.like("a").unwrap();
+        feedback.dislike("b").unwrap();
+
+        let feedback = FeedbackStore::new(&path);
+        assert!(feedback.is_liked("a"));
+        assert!(feedback.get_disliked().contains("b"));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn invalid_feedback_file_is_ignored() {
+        let path = temp_path("invalid");
+        std::fs::write(&path, "not json").unwrap();
+
+        let feedback = FeedbackStore::new(&path);
+        assert!(feedback.get_disliked().is_empty());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,305 @@
+use crate::music_player::music_player_core::music_source::Source;
+use crate::music_player::Error;
+use std::collections::HashSet;
+use std::io::Write;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct HistoryEntry {
+    pub session_id: String,
+    pub video_id: String,
+    pub url: String,
+    pub title: String,
+    pub duration: u64,
+    pub start_time: String,
+    pub listened_s: u64,
+    pub skipped: bool,
+}
+
+struct PlaybackRecord {
+    entry: HistoryEntry,
+    resumed_at: Option<std::time::SystemTime>,
+    listened: std::time::Duration,
+}
+
+pub struct HistoryDatabase {
+    path: String,
+    session_id: String,
+    current: Option<PlaybackRecord>,
+}
+
+impl HistoryDatabase {
+    pub fn new(path: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            session_id: chrono::Local::now().to_rfc3339(),
+            current: None,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.path.is_empty()
+    }
+
+    pub fn start_entry(&mut self, source: &Source) {
+        let video_id = match source {
+            Source::Remote(remote_src) => remote_src.video_id.to_string(),
+            _ => "".to_string(),
+        };
+
+        self.current = Some(PlaybackRecord {
+            entry: HistoryEntry {
+                session_id: self.session_id.to_string(),
+                video_id,
+                url: source.to_url(),
+                title: source.get_title().to_string(),
+                duration: source.get_length(),
+                start_time: chrono::Local::now().to_rfc3339(),
+                listened_s: 0,
+                skipped: false,
+            },
+            resumed_at: Some(std::time::SystemTime::now()),
+            listened: std::time::Duration::ZERO,
+        });
+    }
+
+    pub fn pause(&mut self) {
+        if let Some(record) = self.current.as_mut() {
+            if let Some(resumed_at) = record.resumed_at.take() {
+                record.listened += resumed_at.elapsed().unwrap_or_default();
+            }
+        }
+    }
+
+    pub fn resume(&mut self) {
+        if let Some(record) = self.current.as_mut() {
+            if record.resumed_at.is_none() {
+                record.resumed_at = Some(std::time::SystemTime::now());
+            }
+        }
+    }
+
+    pub fn discard_entry(&mut self) {
+        self.current = None;
+    }
+
+    pub fn finish_entry(&mut self, stopped: bool) -> Result<Option<HistoryEntry>, Error> {
+        self.pause();
+
+        let record = match self.current.take() {
+            Some(record) => record,
+            None => return Ok(None),
+        };
+
+        let mut entry = record.entry;
+        entry.listened_s = record.listened.as_secs();
+        if entry.duration != 0 {
+            entry.listened_s = entry.listened_s.min(entry.duration);
+        }
+        entry.skipped = stopped && (entry.duration == 0 || entry.listened_s + 5 < entry.duration);
+
+        log::info!("HistoryDatabase::finish_entry -> {:?}", entry);
+
+        if self.is_enabled() {
+            self.append(&entry)?;
+        }
+
+        Ok(Some(entry))
+    }
+
+    fn append(&self, entry: &HistoryEntry) -> Result<(), Error> {
+        let mut history_file = std::fs::OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(&self.path)?;
+
+        writeln!(history_file, "{}", serde_json::to_string(entry)?)?;
+
+        Ok(())
+    }
+
+    pub fn load_entries(&self) -> Result<Vec<HistoryEntry>, Error> {
+        if !self.is_enabled() || !std::path::Path::new(&self.path).exists() {
+            return Ok(Vec::new());
+        }
+
+        let history = std::fs::read_to_string(&self.path)?;
+        let entries = history
+            .lines()
+            .filter_map(|line| {
+                let entry = serde_json::from_str(line);
+                if entry.is_err() {
+                    log::info!("HistoryDatabase::load_entries -> {:?}", entry);
+                }
+                entry.ok()
+            })
+            .collect();
+
+        Ok(entries)
+    }
+
+    pub fn load_past_sessions(&self, sessions_count: usize) -> Result<Vec<HistoryEntry>, Error> {
+        let entries = self.load_entries()?;
+
+        let mut session_ids: Vec<&String> = Vec::new();
+        for entry in &entries {
+            if entry.session_id != self.session_id && !session_ids.contains(&&entry.session_id) {
+                session_ids.push(&entry.session_id);
+            }
+        }
+        let session_ids: Vec<String> = session_ids
+            .iter()
+            .rev()
+            .take(sessions_count)
+            .map(|session_id| session_id.to_string())
+            .collect();
+
+        Ok(entries
+            .into_iter()
+            .filter(|entry| session_ids.contains(&entry.session_id))
+            .collect())
+    }
+
+    pub fn load_video_ids(&self) -> Result<HashSet<String>, Error> {
+        Ok(self
+            .load_entries()?
+            .into_iter()
+            .map(|entry| entry.video_id)
+            .filter(|video_id| !video_id.is_empty())
+            .collect())
+    }
+
+    pub fn entries_into_lines(entries: &[HistoryEntry]) -> Vec<String> {
+        let mut lines = Vec::new();
+        let mut session_id = "";
+
+        for entry in entries {
+            if entry.session_id != session_id {
+                session_id = &entry.session_id;
+                lines.push(format!("Session: {session_id}"));
+            }
+
+            let skipped = if entry.skipped { " skipped" } else { "" };
+            lines.push(format!(
+                "  {} [{}/{}s{}]",
+                entry.title, entry.listened_s, entry.duration, skipped
+            ));
+        }
+
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> String {
+        let path = crate::utils::temp_path(&format!("history_{name}.jsonl"));
+
+        path.to_string_lossy().to_string()
+    }
+
+    fn entry(session_id: &str, title: &str) -> HistoryEntry {
+        HistoryEntry {
+            session_id: session_id.to_string(),
+            video_id: title.to_string(),
+            url: format!("https://www.youtube.com/watch?v={title}"),
+            title: title.to_string(),
+            duration: 100,
+            start_time: "".to_string(),
+            listened_s: 0,
+            skipped: false,
+        }
+    }
+
+    #[test]
+    fn finish_entry_marks_stopped_playback_as_skipped() {
+        let mut history_db = HistoryDatabase::new("");
+        let mut source = Source::new_remote("https://www.youtube.com/watch?v=dQw4w9WgXcQ").unwrap();
+        source.set_title_length(Some("Title".to_string()), Some(100));
+
+        history_db.start_entry(&source);
+        let entry = history_db.finish_entry(true).unwrap().unwrap();
+
+        assert_eq!(entry.video_id, "dQw4w9WgXcQ");
+        assert_eq!(entry.title, "Title");
+        assert!(entry.skipped);
+
+        history_db.start_entry(&source);
+        let entry = history_db.finish_entry(false).unwrap().unwrap();
+        assert!(!entry.skipped);
+
+        assert!(history_db.finish_entry(true).unwrap().is_none());
+    }
+
+    #[test]
+    fn discarded_entry_is_not_recorded() {
+        let path = temp_path("discard");
+        let mut history_db = HistoryDatabase::new(&path);
+        let source = Source::new_remote("https://www.youtube.com/watch?v=dQw4w9WgXcQ").unwrap();
+
+        history_db.start_entry(&source);
+        history_db.discard_entry();
+
+        assert!(history_db.finish_entry(true).unwrap().is_none());
+        assert!(history_db.load_entries().unwrap().is_empty());
+    }
+
+    #[test]
+    fn load_past_sessions_skips_current_session() {
+        let path = temp_path("sessions");
+        let history_db = HistoryDatabase::new(&path);
+
+        for entry in [
+            entry("a", "a1"),
+            entry("b", "b1"),
+            entry("b", "b2"),
+            entry(&history_db.session_id, "current"),
+            entry("c", "c1"),
+        ] {
+            history_db.append(&entry).unwrap();
+        }
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"not json\n")
+            .unwrap();
+
+        assert_eq!(history_db.load_entries().unwrap().len(), 5);
+
+        let titles: Vec<String> = history_db
+            .load_past_sessions(2)
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.title)
+            .collect();
+        assert_eq!(titles, vec!["b1", "b2", "c1"]);
+
+        let video_ids = history_db.load_video_ids().unwrap();
+        assert!(video_ids.contains("a1") && video_ids.contains("current"));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn entries_into_lines_groups_by_session() {
+        let mut skipped = entry("b", "b1");
+        skipped.listened_s = 10;
+        skipped.skipped = true;
+
+        let lines =
+            HistoryDatabase::entries_into_lines(&[entry("a", "a1"), entry("a", "a2"), skipped]);
+
+        assert_eq!(
+            lines,
+            vec![
+                "Session: a",
+                "  a1 [0/100s]",
+                "  a2 [0/100s]",
+                "Session: b",
+                "  b1 [10/100s skipped]",
+            ]
+        );
+    }
+}
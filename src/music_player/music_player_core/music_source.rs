@@ -1,14 +1,41 @@
 use crate::music_player::Error;
 use crate::utils;
-
+use audio_stream::AudioStreamPreferences;
+use autoplay_filter::AutoplayFilter;
+use recommendation::{RecommendationConfig, RelatedCandidate};
+use std::collections::{HashSet, VecDeque};
+
+pub mod audio_cache;
+pub mod audio_stream;
+pub mod autoplay_filter;
+pub mod download;
+pub mod lyrics;
+pub mod playlist_file;
+pub mod recommendation;
+pub mod sponsorblock;
+
+const STREAM_URL_EXPIRY_MARGIN_S: u64 = 900;
+
+#[derive(Clone)]
 pub struct RemoteSourceProcessor {
     piped_api_domains: Vec<String>,
     piped_api_domain_index: usize,
     invidious_api_domains: Vec<String>,
     invidious_api_domain_index: usize,
     duration_limit: u64,
+    duration_min: u64,
+    accepted_genres: Vec<String>,
+    genre_check: bool,
     piped_api_domain_index_start: usize,
     invidious_api_domain_index_start: usize,
+    excluded_video_ids: HashSet<String>,
+    disliked_video_ids: HashSet<String>,
+    recommendation: RecommendationConfig,
+    recent_uploaders: VecDeque<String>,
+    autoplay_filter: AutoplayFilter,
+    audio_preferences: AudioStreamPreferences,
+    sponsorblock_api: String,
+    sponsorblock_categories: Vec<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -16,24 +43,73 @@ pub struct Remote {
     pub url: String,
     pub video_id: String,
     pub audio_stream_url: String,
+    pub audio_format: String,
+    pub skip_segments: Vec<sponsorblock::Segment>,
+    pub title: String,
+    pub uploader: String,
+    pub length: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Local {
+    pub path: String,
     pub title: String,
     pub length: u64,
+    pub disc_number: Option<u32>,
+    pub track_number: Option<u32>,
 }
 
 #[derive(Debug, Clone)]
-pub struct Local {}
+pub struct Stream {
+    pub url: String,
+    pub title: String,
+    pub length: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchResult {
+    pub url: String,
+    pub title: String,
+    pub uploader: String,
+    pub duration: u64,
+}
+
+impl SearchResult {
+    pub fn to_display_str(&self) -> String {
+        format!(
+            "{} - {} [{}:{:02}]",
+            self.title,
+            self.uploader,
+            self.duration / 60,
+            self.duration % 60
+        )
+    }
+}
 
 #[derive(Debug, Clone)]
 pub enum Source {
     Remote(Remote),
-    _Local(Local),
+    Local(Local),
+    Stream(Stream),
 }
 
+const AUDIO_FILE_EXTENSIONS: [&str; 12] = [
+    "flac", "mp3", "ogg", "opus", "oga", "m4a", "aac", "wav", "wma", "webm", "mka", "aiff",
+];
+
 impl Source {
     pub fn new_remote(path: &str) -> Result<Self, Error> {
         Ok(Source::Remote(Remote::new(path)?))
     }
 
+    pub fn new_local(path: &str) -> Result<Self, Error> {
+        Ok(Source::Local(Local::new(path)?))
+    }
+
+    pub fn new_stream(url: &str) -> Result<Self, Error> {
+        Ok(Source::Stream(Stream::new(url)?))
+    }
+
     pub fn get_remote_source(&self) -> Result<&Remote, Error> {
         match self {
             Source::Remote(remote_src) => Ok(remote_src),
@@ -48,13 +124,63 @@ impl Source {
         }
     }
 
+    pub fn get_title(&self) -> &str {
+        match self {
+            Source::Remote(remote_src) => &remote_src.title,
+            Source::Local(local_src) => &local_src.title,
+            Source::Stream(stream_src) => &stream_src.title,
+        }
+    }
+
+    pub fn get_length(&self) -> u64 {
+        match self {
+            Source::Remote(remote_src) => remote_src.length,
+            Source::Local(local_src) => local_src.length,
+            Source::Stream(stream_src) => stream_src.length,
+        }
+    }
+
+    pub fn set_title_length(&mut self, new_title: Option<String>, new_length: Option<u64>) {
+        match self {
+            Source::Remote(Remote { title, length, .. })
+            | Source::Local(Local { title, length, .. })
+            | Source::Stream(Stream { title, length, .. }) => {
+                if let Some(new_title) = new_title {
+                    *title = new_title;
+                }
+                if let Some(new_length) = new_length {
+                    *length = new_length;
+                }
+            }
+        }
+    }
+
+    pub fn to_url(&self) -> String {
+        match self {
+            Source::Remote(remote_src) => {
+                format!("https://www.youtube.com/watch?v={}", remote_src.video_id)
+            }
+            Source::Local(local_src) => local_src.path.to_string(),
+            Source::Stream(stream_src) => stream_src.url.to_string(),
+        }
+    }
+
     pub fn is_valid_source_path(path: &str) -> bool {
         let mut valid_path = false;
+        valid_path |= Local::is_valid_path(path);
+        valid_path |= Local::is_valid_dir_path(path);
+        valid_path |= playlist_file::is_valid_playlist_file_path(path);
         valid_path |= Remote::url_into_video_id(path).is_ok();
         valid_path |= Remote::url_into_playlist_id(path).is_ok();
 
         valid_path
     }
+
+    pub fn looks_like_path(path: &str) -> bool {
+        path.starts_with(['/', '~', '.'])
+            || Local::has_audio_extension(std::path::Path::new(path))
+            || playlist_file::has_playlist_file_extension(path)
+    }
 }
 
 impl Remote {
@@ -63,11 +189,33 @@ impl Remote {
             url: path.to_string(),
             video_id: Remote::url_into_video_id(path)?,
             audio_stream_url: "".to_string(),
+            audio_format: "".to_string(),
+            skip_segments: Vec::new(),
             title: "".to_string(),
+            uploader: "".to_string(),
             length: 0,
         })
     }
 
+    pub fn is_stream_url_expired(&self) -> bool {
+        let expire = self
+            .audio_stream_url
+            .split(['?', '&'])
+            .find_map(|param| param.strip_prefix("expire="))
+            .and_then(|expire| expire.parse::<u64>().ok());
+        let expire = match expire {
+            Some(expire) => expire,
+            None => return false,
+        };
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|now| now.as_secs())
+            .unwrap_or_default();
+
+        now + STREAM_URL_EXPIRY_MARGIN_S >= expire
+    }
+
     pub fn url_into_video_id(url: &str) -> Result<String, Error> {
         if url.contains("v=") {
             let split = url.split("v=");
@@ -95,6 +243,246 @@ impl Remote {
     }
 }
 
+impl Stream {
+    pub fn new(url: &str) -> Result<Self, Error> {
+        if !Stream::is_valid_url(url) {
+            return Err(Error::InvalidStreamUrl(format!(
+                "Not a valid http(s) stream url: {url}"
+            )));
+        }
+
+        Ok(Stream {
+            url: url.to_string(),
+            title: url.to_string(),
+            length: 0,
+        })
+    }
+
+    pub fn is_valid_url(url: &str) -> bool {
+        url.starts_with("http://") || url.starts_with("https://")
+    }
+}
+
+impl Local {
+    pub fn new(path: &str) -> Result<Self, Error> {
+        if !Local::is_valid_path(path) {
+            return Err(Error::InvalidLocalPath(format!(
+                "Not a valid audio file: {path}"
+            )));
+        }
+
+        Ok(Local {
+            path: path.to_string(),
+            title: "".to_string(),
+            length: 0,
+            disc_number: None,
+            track_number: None,
+        })
+    }
+
+    pub fn is_valid_path(path: &str) -> bool {
+        let path = std::path::Path::new(path);
+
+        path.is_file() && Local::has_audio_extension(path)
+    }
+
+    pub fn is_valid_dir_path(path: &str) -> bool {
+        std::path::Path::new(path).is_dir()
+    }
+
+    pub fn dir_to_local_vec(path: &str) -> Result<Vec<Source>, Error> {
+        log::info!("Local::dir_to_local_vec -> {:?}", path);
+
+        let mut audio_files = Vec::new();
+        Local::find_audio_files(std::path::Path::new(path), &mut audio_files)?;
+
+        let mut dir = Vec::new();
+        for audio_file in audio_files {
+            match Local::new(&audio_file.to_string_lossy()) {
+                Ok(mut local_src) => {
+                    local_src.set_title_length()?;
+                    dir.push(local_src);
+                }
+                Err(err) => log::info!("Local::dir_to_local_vec -> {:?}", err),
+            }
+        }
+
+        dir.sort_by_cached_key(Local::sort_key);
+
+        log::info!("Local::dir_to_local_vec -> {} audio files", dir.len());
+
+        if dir.is_empty() {
+            return Err(Error::InvalidLocalPath(format!(
+                "No audio files found in: {path}"
+            )));
+        }
+
+        Ok(dir.into_iter().map(Source::Local).collect())
+    }
+
+    fn find_audio_files(
+        dir: &std::path::Path,
+        audio_files: &mut Vec<std::path::PathBuf>,
+    ) -> Result<(), Error> {
+        for entry in std::fs::read_dir(dir)? {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(err) => {
+                    log::info!("Local::find_audio_files -> {:?}::{:?}", dir, err);
+                    continue;
+                }
+            };
+            let file_type = match entry.file_type() {
+                Ok(file_type) => file_type,
+                Err(err) => {
+                    log::info!("Local::find_audio_files -> {:?}::{:?}", entry.path(), err);
+                    continue;
+                }
+            };
+            let path = entry.path();
+
+            // Symlinked directories are skipped so that link loops can't recurse forever
+            if file_type.is_dir() {
+                if let Err(err) = Local::find_audio_files(&path, audio_files) {
+                    log::info!("Local::find_audio_files -> {:?}::{:?}", path, err);
+                }
+            } else if path.is_file() && Local::has_audio_extension(&path) {
+                audio_files.push(path);
+            }
+        }
+
+        Ok(())
+    }
+
+    // Files in the same directory are ordered by disc/track tag, then by the leading
+    // number of the file name, e.g. "03 - Title.flac", and unnumbered files go last
+    fn sort_key(&self) -> (std::path::PathBuf, bool, u32, u32, std::ffi::OsString) {
+        let path = std::path::Path::new(&self.path);
+        let track_number = self.track_number.or_else(|| Local::file_name_number(path));
+
+        (
+            path.parent().unwrap_or(path).to_path_buf(),
+            track_number.is_none(),
+            self.disc_number.unwrap_or(1),
+            track_number.unwrap_or_default(),
+            path.file_name().unwrap_or_default().to_os_string(),
+        )
+    }
+
+    fn file_name_number(path: &std::path::Path) -> Option<u32> {
+        let file_name = path.file_name()?.to_str()?;
+        let digits: String = file_name
+            .chars()
+            .take_while(|c| c.is_ascii_digit())
+            .collect();
+
+        digits.parse().ok()
+    }
+
+    // Track and disc tags are often stored as "3/12"
+    fn parse_tag_number(value: &str) -> Option<u32> {
+        value.split('/').next()?.trim().parse().ok()
+    }
+
+    fn has_audio_extension(path: &std::path::Path) -> bool {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| AUDIO_FILE_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+            .unwrap_or(false)
+    }
+
+    pub fn set_title_length(&mut self) -> Result<(), Error> {
+        let result = self._set_title_length();
+
+        if result.is_err() {
+            log::info!("Local::set_title_length -> {:?}", result);
+
+            self.title = Local::path_into_title(&self.path);
+            self.length = 0;
+        }
+
+        Ok(())
+    }
+
+    fn path_into_title(path: &str) -> String {
+        std::path::Path::new(path)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or(path.to_string())
+    }
+
+    fn _set_title_length(&mut self) -> Result<(), Error> {
+        use symphonia::core::formats::FormatOptions;
+        use symphonia::core::io::MediaSourceStream;
+        use symphonia::core::meta::{MetadataOptions, StandardTagKey};
+        use symphonia::core::probe::Hint;
+
+        let path = std::path::Path::new(&self.path);
+        let file = std::fs::File::open(path)?;
+        let media_source_stream = MediaSourceStream::new(Box::new(file), Default::default());
+
+        let mut hint = Hint::new();
+        if let Some(extension) = path.extension().and_then(|ext| ext.to_str()) {
+            hint.with_extension(extension);
+        }
+
+        let mut probed = symphonia::default::get_probe().format(
+            &hint,
+            media_source_stream,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )?;
+
+        let mut title = None;
+        let mut artist = None;
+        let mut disc_number = None;
+        let mut track_number = None;
+
+        let mut tags = Vec::new();
+        if let Some(metadata) = probed.metadata.get() {
+            if let Some(revision) = metadata.current() {
+                tags.extend(revision.tags().to_owned());
+            }
+        }
+        if let Some(revision) = probed.format.metadata().current() {
+            tags.extend(revision.tags().to_owned());
+        }
+
+        for tag in tags {
+            match tag.std_key {
+                Some(StandardTagKey::TrackTitle) => title = Some(tag.value.to_string()),
+                Some(StandardTagKey::Artist) => artist = Some(tag.value.to_string()),
+                Some(StandardTagKey::DiscNumber) => {
+                    disc_number = Local::parse_tag_number(&tag.value.to_string())
+                }
+                Some(StandardTagKey::TrackNumber) => {
+                    track_number = Local::parse_tag_number(&tag.value.to_string())
+                }
+                _ => (),
+            }
+        }
+
+        self.title = match (artist, title) {
+            (Some(artist), Some(title)) => format!("{artist} - {title}"),
+            (None, Some(title)) => title,
+            _ => Local::path_into_title(&self.path),
+        };
+        self.disc_number = disc_number;
+        self.track_number = track_number;
+
+        if let Some(track) = probed.format.default_track() {
+            let codec_params = &track.codec_params;
+            if let (Some(time_base), Some(n_frames)) =
+                (codec_params.time_base, codec_params.n_frames)
+            {
+                self.length = time_base.calc_time(n_frames).seconds;
+            }
+        }
+
+        Ok(())
+    }
+}
+
 impl RemoteSourceProcessor {
     pub fn new(
         piped_api_domains: Vec<String>,
@@ -109,11 +497,145 @@ impl RemoteSourceProcessor {
             invidious_api_domains,
             invidious_api_domain_index,
             duration_limit,
+            duration_min: 0,
+            accepted_genres: vec!["Music".to_string()],
+            genre_check: true,
             piped_api_domain_index_start: piped_api_domain_index,
             invidious_api_domain_index_start: invidious_api_domain_index,
+            excluded_video_ids: HashSet::new(),
+            disliked_video_ids: HashSet::new(),
+            recommendation: RecommendationConfig::default(),
+            recent_uploaders: VecDeque::new(),
+            autoplay_filter: AutoplayFilter::default(),
+            audio_preferences: AudioStreamPreferences::default(),
+            sponsorblock_api: "".to_string(),
+            sponsorblock_categories: Vec::new(),
         })
     }
 
+    pub fn stream_resolver(&self) -> Self {
+        Self {
+            piped_api_domains: self.piped_api_domains.clone(),
+            piped_api_domain_index: self.piped_api_domain_index,
+            invidious_api_domains: Vec::new(),
+            invidious_api_domain_index: 0,
+            duration_limit: self.duration_limit,
+            duration_min: self.duration_min,
+            accepted_genres: Vec::new(),
+            genre_check: false,
+            piped_api_domain_index_start: self.piped_api_domain_index,
+            invidious_api_domain_index_start: 0,
+            excluded_video_ids: HashSet::new(),
+            disliked_video_ids: HashSet::new(),
+            recommendation: RecommendationConfig::default(),
+            recent_uploaders: VecDeque::new(),
+            autoplay_filter: AutoplayFilter::default(),
+            audio_preferences: self.audio_preferences.clone(),
+            sponsorblock_api: self.sponsorblock_api.clone(),
+            sponsorblock_categories: self.sponsorblock_categories.clone(),
+        }
+    }
+
+    pub fn set_recommendation_config(&mut self, recommendation: RecommendationConfig) {
+        log::info!(
+            "RemoteSourceProcessor::set_recommendation_config -> {:?}",
+            recommendation
+        );
+
+        self.recommendation = recommendation;
+    }
+
+    pub fn set_excluded_video_ids(&mut self, excluded_video_ids: HashSet<String>) {
+        log::info!(
+            "RemoteSourceProcessor::set_excluded_video_ids -> {}",
+            excluded_video_ids.len()
+        );
+
+        self.excluded_video_ids = excluded_video_ids;
+    }
+
+    pub fn set_disliked_video_ids(&mut self, disliked_video_ids: HashSet<String>) {
+        log::info!(
+            "RemoteSourceProcessor::set_disliked_video_ids -> {}",
+            disliked_video_ids.len()
+        );
+
+        self.disliked_video_ids = disliked_video_ids;
+    }
+
+    pub fn set_duration_min(&mut self, duration_min: u64) {
+        self.duration_min = duration_min;
+    }
+
+    pub fn set_genre_filter(&mut self, accepted_genres: Vec<String>, genre_check: bool) {
+        log::info!(
+            "RemoteSourceProcessor::set_genre_filter -> {:?}::{:?}",
+            accepted_genres,
+            genre_check
+        );
+
+        self.accepted_genres = accepted_genres;
+        self.genre_check = genre_check;
+    }
+
+    pub fn set_audio_preferences(&mut self, audio_preferences: AudioStreamPreferences) {
+        log::info!(
+            "RemoteSourceProcessor::set_audio_preferences -> {:?}",
+            audio_preferences
+        );
+
+        self.audio_preferences = audio_preferences;
+    }
+
+    pub fn set_sponsorblock(&mut self, sponsorblock_api: String, categories: Vec<String>) {
+        log::info!(
+            "RemoteSourceProcessor::set_sponsorblock -> {:?}::{:?}",
+            sponsorblock_api,
+            categories
+        );
+
+        self.sponsorblock_api = sponsorblock_api;
+        self.sponsorblock_categories = categories;
+    }
+
+    pub fn set_skip_segments(&self, source: &mut Remote) {
+        if self.sponsorblock_api.is_empty() || self.sponsorblock_categories.is_empty() {
+            return;
+        }
+
+        let result = sponsorblock::fetch_segments(
+            &self.sponsorblock_api,
+            &source.video_id,
+            &self.sponsorblock_categories,
+        );
+        log::info!(
+            "RemoteSourceProcessor::set_skip_segments -> {:?}::{:?}",
+            source.video_id,
+            result
+        );
+
+        if let Ok(segments) = result {
+            source.skip_segments = segments;
+        }
+    }
+
+    pub fn set_autoplay_filter(&mut self, autoplay_filter: AutoplayFilter) {
+        log::info!(
+            "RemoteSourceProcessor::set_autoplay_filter -> {:?}",
+            autoplay_filter
+        );
+
+        self.autoplay_filter = autoplay_filter;
+    }
+
+    pub fn set_video_disliked(&mut self, video_id: &str, disliked: bool) {
+        if disliked {
+            self.disliked_video_ids.insert(video_id.to_string());
+        } else {
+            self.disliked_video_ids.remove(video_id);
+        }
+    }
+
     pub fn next_piped_api_domains_index(&mut self) -> Result<(), Error> {
         let mut i = self.piped_api_domain_index;
         i += 1;
@@ -159,31 +681,39 @@ impl RemoteSourceProcessor {
             &source.video_id
         );
 
-        let mut response: serde_json::Value = utils::reqwest_get(&request_url)?.json()?;
+        let response: serde_json::Value = utils::reqwest_get(&request_url)?.json()?;
 
-        let _test_audio_streams = response
+        let audio_streams = response
             .get("audioStreams")
+            .and_then(|audio_streams| audio_streams.as_array())
             .ok_or_else(|| Error::OtherError(format!("{:?}", response.to_string())))?;
-        let audio_streams: &mut Vec<serde_json::Value> = response
-            .get_mut("audioStreams")
-            .unwrap()
-            .as_array_mut()
-            .unwrap();
-
-        audio_streams.sort_by_key(|x| x.get("bitrate").unwrap().as_u64().unwrap());
-        let audio_stream = audio_streams.last().unwrap();
+
+        let audio_stream =
+            audio_stream::select_audio_stream(audio_streams, &self.audio_preferences)
+                .ok_or_else(|| Error::OtherError(format!("{:?}", response.to_string())))?;
         let music_url = audio_stream.get("url").unwrap();
-        source.audio_stream_url = music_url.to_string();
-        source.audio_stream_url = source.audio_stream_url.replace("\"", "");
-        let music_title = response.get("title").unwrap();
-        source.title = music_title.to_string();
+        source.audio_stream_url = music_url.as_str().unwrap_or_default().to_string();
+        source.audio_format = audio_stream::describe_audio_stream(audio_stream);
+        let music_title = response.get("title").and_then(|title| title.as_str());
+        source.title = music_title.unwrap_or_default().to_string();
         let duration = response.get("duration").unwrap();
         source.length = duration.as_u64().unwrap();
 
+        if let Some(uploader) = response.get("uploader").and_then(|x| x.as_str()) {
+            source.uploader = uploader.to_string();
+        }
+
         self.piped_api_domain_index_start = self.piped_api_domain_index;
         Ok(())
     }
 
+    pub fn record_uploader(&mut self, uploader: &str) {
+        self.recent_uploaders.push_back(uploader.to_string());
+        while self.recent_uploaders.len() > self.recommendation.diversity_window.max(1) {
+            self.recent_uploaders.pop_front();
+        }
+    }
+
     pub fn get_video_genre(&mut self, source: &Remote) -> Result<String, Error> {
         let mut result = self._get_video_genre(source);
 
@@ -275,12 +805,41 @@ impl RemoteSourceProcessor {
             .ok_or_else(|| Error::OtherError(format!("{:?}", response.to_string())))?;
         let related_streams: &Vec<serde_json::Value> = related_streams.as_array().unwrap();
 
-        for related_stream in related_streams {
+        let mut candidates: Vec<RelatedCandidate> = related_streams
+            .iter()
+            .enumerate()
+            .filter(|(_, related_stream)| {
+                related_stream
+                    .get("url")
+                    .and_then(|url| url.as_str())
+                    .is_some_and(|url| !url.contains("/playlist"))
+            })
+            .map(|(index, related_stream)| RelatedCandidate {
+                index,
+                uploader: related_stream
+                    .get("uploaderName")
+                    .and_then(|uploader| uploader.as_str())
+                    .unwrap_or_default()
+                    .to_string(),
+                score: 0.0,
+            })
+            .collect();
+        recommendation::score_candidates(
+            &mut candidates,
+            &self.recent_uploaders,
+            &self.recommendation,
+        );
+
+        for candidate in candidates {
+            let related_stream = &related_streams[candidate.index];
             let related_video_url = related_stream.get("url").unwrap();
             let related_video_url = related_video_url.as_str().unwrap();
-            if related_video_url.contains("/playlist") {
-                continue;
-            }
+            log::info!(
+                "RemoteSourceProcessor::get_related_video_url -> candidate {:?}::{:?}::{:.2}",
+                related_video_url,
+                candidate.uploader,
+                candidate.score
+            );
             if self.check_filters_for_related_video_url(
                 related_video_url,
                 related_stream,
@@ -308,21 +867,34 @@ impl RemoteSourceProcessor {
         match new_remote_src {
             Source::Remote(remote_src) => {
                 let video_id = &remote_src.video_id;
-                if played_video_ids.contains(video_id) {
+                if played_video_ids.contains(video_id)
+                    || self.excluded_video_ids.contains(video_id)
+                    || self.disliked_video_ids.contains(video_id)
+                    || !self.check_autoplay_filter(stream_json)
+                {
                     return Ok(false);
                 } else if video_id.contains("list=") {
                     //MIX or playlist
                     return Ok(false);
-                } else if stream_json // possiblty of MIX with no duration
+                }
+
+                let duration = stream_json // possiblty of MIX with no duration
                     .get("duration")
                     .ok_or_else(|| Error::OtherError(format!("{:?}", stream_json)))?
-                    .as_u64()
-                    .unwrap_or(self.duration_limit + 1)
-                    > self.duration_limit
+                    .as_u64();
+                if duration.unwrap_or(self.duration_limit + 1) > self.duration_limit
+                    || duration.unwrap_or_default() < self.duration_min
                 {
                     return Ok(false);
-                } else if !self.get_video_genre(&remote_src)?.contains("Music") {
-                    return Ok(false);
+                } else if self.genre_check && !self.accepted_genres.is_empty() {
+                    let genre = self.get_video_genre(&remote_src)?;
+                    if !self
+                        .accepted_genres
+                        .iter()
+                        .any(|accepted_genre| genre.contains(accepted_genre.as_str()))
+                    {
+                        return Ok(false);
+                    }
                 }
             }
             _ => panic!(),
@@ -330,6 +902,19 @@ impl RemoteSourceProcessor {
         Ok(true)
     }
 
+    fn check_autoplay_filter(&self, stream_json: &serde_json::Value) -> bool {
+        let get_str = |key: &str| {
+            stream_json
+                .get(key)
+                .and_then(|value| value.as_str())
+                .unwrap_or_default()
+        };
+        let channel_id = AutoplayFilter::channel_id_from_url(get_str("uploaderUrl"));
+
+        self.autoplay_filter
+            .is_allowed(get_str("uploaderName"), channel_id, get_str("title"))
+    }
+
     pub fn playlist_to_remote_vec(&mut self, playlist_id: &str) -> Result<Vec<Source>, Error> {
         let result = self._playlist_to_remote_vec(playlist_id);
 
@@ -363,7 +948,10 @@ impl RemoteSourceProcessor {
                     url,
                     video_id,
                     audio_stream_url: "".to_string(),
+                    audio_format: "".to_string(),
+                    skip_segments: Vec::new(),
                     title: "".to_string(),
+                    uploader: "".to_string(),
                     length: 0,
                 }));
             }
@@ -388,6 +976,54 @@ impl RemoteSourceProcessor {
         Ok(playlist)
     }
 
+    pub fn search(&mut self, query: &str) -> Result<Vec<SearchResult>, Error> {
+        let result = self._search(query);
+
+        if result.is_err() {
+            log::info!("RemoteSourceProcessor::search -> {:?}", result);
+        }
+
+        Ok(result?)
+    }
+
+    pub fn _search(&mut self, query: &str) -> Result<Vec<SearchResult>, Error> {
+        let request_url = format!(
+            "{}/search?q={}&filter=music_songs",
+            self.get_piped_api_domain(),
+            urlencoding::encode(query)
+        );
+
+        let response: serde_json::Value = utils::reqwest_get(&request_url)?.json()?;
+
+        let items = response
+            .get("items")
+            .and_then(|items| items.as_array())
+            .ok_or_else(|| Error::OtherError(format!("{:?}", response.to_string())))?;
+
+        let mut results = Vec::new();
+        for item in items {
+            let url = match item.get("url").and_then(|url| url.as_str()) {
+                Some(url) if url.contains("watch?v=") => url.to_string(),
+                _ => continue,
+            };
+            let title = item.get("title").and_then(|title| title.as_str());
+            let uploader = item
+                .get("uploaderName")
+                .and_then(|uploader| uploader.as_str());
+            let duration = item.get("duration").and_then(|duration| duration.as_i64());
+
+            results.push(SearchResult {
+                url,
+                title: title.unwrap_or_default().to_string(),
+                uploader: uploader.unwrap_or_default().to_string(),
+                duration: duration.unwrap_or_default().max(0) as u64,
+            });
+        }
+
+        self.piped_api_domain_index_start = self.piped_api_domain_index;
+        Ok(results)
+    }
+
     pub fn fetch_piped_api_domains(&mut self) -> Result<(), Error> {
         log::info!("RemoteSourceProcessor::fetch_piped_api_domains");
 
@@ -528,3 +1164,110 @@ impl RemoteSourceProcessor {
         invalid
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local(path: &str, disc_number: Option<u32>, track_number: Option<u32>) -> Local {
+        Local {
+            path: path.to_string(),
+            title: "".to_string(),
+            length: 0,
+            disc_number,
+            track_number,
+        }
+    }
+
+    #[test]
+    fn local_files_are_ordered_by_tags_then_file_name() {
+        let mut dir = [
+            local("/album/b.flac", None, None),
+            local("/album/10 - ten.flac", None, None),
+            local("/album/x.flac", Some(2), Some(1)),
+            local("/album/a.flac", None, None),
+            local("/album/9 - nine.flac", None, None),
+            local("/album/y.flac", Some(1), Some(2)),
+            local("/album/cd2/1.flac", None, None),
+        ];
+
+        dir.sort_by_cached_key(Local::sort_key);
+
+        let paths: Vec<&str> = dir
+            .iter()
+            .map(|local_src| local_src.path.as_str())
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                "/album/y.flac",
+                "/album/9 - nine.flac",
+                "/album/10 - ten.flac",
+                "/album/x.flac",
+                "/album/a.flac",
+                "/album/b.flac",
+                "/album/cd2/1.flac",
+            ]
+        );
+    }
+
+    fn remote_src_proc() -> RemoteSourceProcessor {
+        let mut remote_src_proc = RemoteSourceProcessor::new(vec![], 0, vec![], 0, 600).unwrap();
+        remote_src_proc.set_genre_filter(vec![], false);
+
+        remote_src_proc
+    }
+
+    #[test]
+    fn disliked_related_video_is_rejected() {
+        let mut remote_src_proc = remote_src_proc();
+        let video_url = "https://www.youtube.com/watch?v=dQw4w9WgXcQ";
+        let stream_json = serde_json::json!({ "duration": 212 });
+
+        assert!(remote_src_proc
+            .check_filters_for_related_video_url(video_url, &stream_json, &vec![])
+            .unwrap());
+
+        remote_src_proc.set_video_disliked("dQw4w9WgXcQ", true);
+        assert!(!remote_src_proc
+            .check_filters_for_related_video_url(video_url, &stream_json, &vec![])
+            .unwrap());
+
+        remote_src_proc.set_video_disliked("dQw4w9WgXcQ", false);
+        assert!(remote_src_proc
+            .check_filters_for_related_video_url(video_url, &stream_json, &vec![])
+            .unwrap());
+    }
+
+    #[test]
+    fn empty_genre_list_accepts_all_genres() {
+        let mut remote_src_proc = remote_src_proc();
+        remote_src_proc.set_genre_filter(vec![], true);
+        let video_url = "https://www.youtube.com/watch?v=dQw4w9WgXcQ";
+
+        assert!(remote_src_proc
+            .check_filters_for_related_video_url(
+                video_url,
+                &serde_json::json!({ "duration": 212 }),
+                &vec![]
+            )
+            .unwrap());
+    }
+
+    #[test]
+    fn duration_limits_reject_related_video() {
+        let mut remote_src_proc = remote_src_proc();
+        remote_src_proc.set_duration_min(60);
+        let video_url = "https://www.youtube.com/watch?v=dQw4w9WgXcQ";
+
+        for duration in [30, 601] {
+            assert!(!remote_src_proc
+                .check_filters_for_related_video_url(
+                    video_url,
+                    &serde_json::json!({ "duration": duration }),
+                    &vec![]
+                )
+                .unwrap());
+        }
+    }
+}
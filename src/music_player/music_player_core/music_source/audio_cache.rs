@@ -0,0 +1,350 @@
+use super::sponsorblock::Segment;
+use super::Remote;
+use crate::music_player::Error;
+use crate::utils;
+use std::path::PathBuf;
+
+// Partial downloads untouched for this long are left over from a crashed instance
+const STALE_PARTIAL_DOWNLOAD_AGE: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct CacheEntry {
+    pub video_id: String,
+    pub title: String,
+    #[serde(default)]
+    pub uploader: String,
+    pub length: u64,
+    pub audio_format: String,
+    pub skip_segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AudioCache {
+    dir: String,
+    max_size_bytes: u64,
+    store_send: Option<crossbeam::channel::Sender<Remote>>,
+}
+
+impl AudioCache {
+    pub fn new(dir: &str, max_size_mb: u64) -> Self {
+        let audio_cache = Self {
+            dir: dir.to_string(),
+            max_size_bytes: max_size_mb * 1024 * 1024,
+            store_send: None,
+        };
+
+        if audio_cache.is_enabled() {
+            let result = audio_cache.remove_stale_partial_downloads();
+            log::info!("AudioCache::new -> {:?}::{:?}", dir, result);
+        }
+
+        audio_cache
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.dir.is_empty()
+    }
+
+    pub fn start(&mut self) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        let (s, r) = crossbeam::channel::unbounded::<Remote>();
+        let audio_cache = Self {
+            dir: self.dir.to_string(),
+            max_size_bytes: self.max_size_bytes,
+            store_send: None,
+        };
+        std::thread::spawn(move || {
+            while let Ok(source) = r.recv() {
+                let result = audio_cache.store(&source);
+                log::info!("AudioCache::start -> {:?}::{:?}", source.video_id, result);
+            }
+        });
+
+        self.store_send = Some(s);
+    }
+
+    pub fn store_in_background(&self, source: &Remote) {
+        if let Some(store_send) = &self.store_send {
+            if !self.contains(&source.video_id) {
+                store_send.send(source.clone()).unwrap();
+            }
+        }
+    }
+
+    fn audio_path(&self, video_id: &str) -> PathBuf {
+        PathBuf::from(&self.dir).join(format!("{video_id}.audio"))
+    }
+
+    fn entry_path(&self, video_id: &str) -> PathBuf {
+        PathBuf::from(&self.dir).join(format!("{video_id}.json"))
+    }
+
+    fn partial_path(&self, video_id: &str) -> PathBuf {
+        PathBuf::from(&self.dir).join(format!("{video_id}.part"))
+    }
+
+    pub fn contains(&self, video_id: &str) -> bool {
+        self.is_enabled()
+            && self.audio_path(video_id).is_file()
+            && self.entry_path(video_id).is_file()
+    }
+
+    fn load_entry(&self, video_id: &str) -> Result<CacheEntry, Error> {
+        let entry = std::fs::read_to_string(self.entry_path(video_id))?;
+
+        Ok(serde_json::from_str(&entry)?)
+    }
+
+    pub fn apply_to(&self, source: &mut Remote) -> bool {
+        if !self.contains(&source.video_id) {
+            return false;
+        }
+
+        let entry = match self.load_entry(&source.video_id) {
+            Ok(entry) => entry,
+            Err(err) => {
+                log::info!("AudioCache::apply_to -> {:?}", err);
+                return false;
+            }
+        };
+
+        let audio_path = self.audio_path(&source.video_id);
+        if let Ok(audio_file) = std::fs::File::options().append(true).open(&audio_path) {
+            let _ = audio_file.set_modified(std::time::SystemTime::now());
+        }
+
+        source.audio_stream_url = audio_path.to_string_lossy().to_string();
+        source.title = entry.title;
+        source.uploader = entry.uploader;
+        source.length = entry.length;
+        source.audio_format = entry.audio_format;
+        source.skip_segments = entry.skip_segments;
+
+        true
+    }
+
+    pub fn store(&self, source: &Remote) -> Result<(), Error> {
+        if !self.is_enabled() || self.contains(&source.video_id) {
+            return Ok(());
+        }
+
+        std::fs::create_dir_all(&self.dir)?;
+
+        let entry = CacheEntry {
+            video_id: source.video_id.to_string(),
+            title: source.title.to_string(),
+            uploader: source.uploader.to_string(),
+            length: source.length,
+            audio_format: source.audio_format.to_string(),
+            skip_segments: source.skip_segments.clone(),
+        };
+        let entry = serde_json::to_string_pretty(&entry)?;
+
+        let partial_path = self.partial_path(&source.video_id);
+        let mut partial_file = match std::fs::File::options()
+            .write(true)
+            .create_new(true)
+            .open(&partial_path)
+        {
+            Ok(partial_file) => partial_file,
+            Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => return Ok(()),
+            Err(err) => return Err(err.into()),
+        };
+
+        let result = utils::reqwest_download(&source.audio_stream_url, &mut partial_file);
+        drop(partial_file);
+        if let Err(err) = result {
+            std::fs::remove_file(&partial_path)?;
+            return Err(err);
+        }
+
+        // The entry is written last, an audio file without one is never used
+        let audio_path = self.audio_path(&source.video_id);
+        if let Err(err) = std::fs::rename(&partial_path, &audio_path) {
+            std::fs::remove_file(&partial_path)?;
+            return Err(err.into());
+        }
+        if let Err(err) = std::fs::write(self.entry_path(&source.video_id), entry) {
+            std::fs::remove_file(&audio_path)?;
+            return Err(err.into());
+        }
+
+        self.evict(&source.video_id)
+    }
+
+    pub fn cached_video_ids(&self) -> Vec<String> {
+        if !self.is_enabled() {
+            return Vec::new();
+        }
+
+        self.audio_files()
+            .into_iter()
+            .filter_map(|(path, _, _)| Some(path.file_stem()?.to_str()?.to_string()))
+            .filter(|video_id| self.entry_path(video_id).is_file())
+            .collect()
+    }
+
+    fn audio_files(&self) -> Vec<(PathBuf, u64, std::time::SystemTime)> {
+        let read_dir = match std::fs::read_dir(&self.dir) {
+            Ok(read_dir) => read_dir,
+            Err(_) => return Vec::new(),
+        };
+
+        read_dir
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension == "audio")
+            })
+            .filter_map(|path| {
+                let metadata = path.metadata().ok()?;
+                let modified = metadata.modified().ok()?;
+
+                Some((path, metadata.len(), modified))
+            })
+            .collect()
+    }
+
+    fn evict(&self, keep_video_id: &str) -> Result<(), Error> {
+        if self.max_size_bytes == 0 {
+            return Ok(());
+        }
+
+        let mut audio_files = self.audio_files();
+        let mut size: u64 = audio_files.iter().map(|(_, len, _)| len).sum();
+        audio_files.sort_by_key(|(_, _, modified)| *modified);
+
+        for (path, len, _) in audio_files {
+            if size <= self.max_size_bytes {
+                break;
+            }
+
+            let video_id = match path.file_stem().and_then(|stem| stem.to_str()) {
+                Some(video_id) if video_id != keep_video_id => video_id.to_string(),
+                _ => continue,
+            };
+
+            log::info!("AudioCache::evict -> {:?}", video_id);
+            std::fs::remove_file(&path)?;
+            let _ = std::fs::remove_file(self.entry_path(&video_id));
+            size -= len;
+        }
+
+        Ok(())
+    }
+
+    fn remove_stale_partial_downloads(&self) -> Result<(), Error> {
+        for entry in std::fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|extension| extension != "part") {
+                continue;
+            }
+
+            let age = path.metadata()?.modified()?.elapsed().unwrap_or_default();
+            if age >= STALE_PARTIAL_DOWNLOAD_AGE {
+                std::fs::remove_file(path)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_cache(name: &str, max_size_bytes: u64) -> AudioCache {
+        let dir = crate::utils::temp_dir(&format!("audio_cache_{name}"));
+
+        AudioCache {
+            dir: dir.to_string_lossy().to_string(),
+            max_size_bytes,
+            store_send: None,
+        }
+    }
+
+    fn add_entry(audio_cache: &AudioCache, video_id: &str, len: usize, age_s: u64) {
+        let entry = CacheEntry {
+            video_id: video_id.to_string(),
+            title: video_id.to_string(),
+            uploader: "".to_string(),
+            length: 0,
+            audio_format: "".to_string(),
+            skip_segments: Vec::new(),
+        };
+        std::fs::write(
+            audio_cache.entry_path(video_id),
+            serde_json::to_string(&entry).unwrap(),
+        )
+        .unwrap();
+
+        let audio_path = audio_cache.audio_path(video_id);
+        std::fs::write(&audio_path, vec![0; len]).unwrap();
+        let modified = std::time::SystemTime::now() - std::time::Duration::from_secs(age_s);
+        std::fs::File::options()
+            .append(true)
+            .open(&audio_path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+    }
+
+    #[test]
+    fn evict_removes_least_recently_used() {
+        let audio_cache = temp_cache("evict", 250);
+        add_entry(&audio_cache, "oldest", 100, 300);
+        add_entry(&audio_cache, "older", 100, 200);
+        add_entry(&audio_cache, "newest", 100, 100);
+        add_entry(&audio_cache, "kept", 100, 400);
+
+        audio_cache.evict("kept").unwrap();
+
+        let mut cached_video_ids = audio_cache.cached_video_ids();
+        cached_video_ids.sort();
+        assert_eq!(cached_video_ids, vec!["kept", "newest"]);
+        assert!(!audio_cache.entry_path("oldest").exists());
+
+        std::fs::remove_dir_all(&audio_cache.dir).unwrap();
+    }
+
+    #[test]
+    fn apply_to_uses_cached_entry() {
+        let audio_cache = temp_cache("apply", 0);
+        add_entry(&audio_cache, "dQw4w9WgXcQ", 10, 100);
+
+        let mut source = Remote::new("https://www.youtube.com/watch?v=dQw4w9WgXcQ").unwrap();
+        assert!(audio_cache.apply_to(&mut source));
+        assert_eq!(source.title, "dQw4w9WgXcQ");
+        assert!(source.audio_stream_url.ends_with("dQw4w9WgXcQ.audio"));
+
+        std::fs::remove_file(audio_cache.entry_path("dQw4w9WgXcQ")).unwrap();
+        assert!(!audio_cache.contains("dQw4w9WgXcQ"));
+
+        std::fs::remove_dir_all(&audio_cache.dir).unwrap();
+    }
+
+    #[test]
+    fn only_stale_partial_downloads_are_removed() {
+        let audio_cache = temp_cache("partial", 0);
+        std::fs::write(audio_cache.partial_path("fresh"), b"partial").unwrap();
+        std::fs::write(audio_cache.partial_path("stale"), b"partial").unwrap();
+        std::fs::File::options()
+            .append(true)
+            .open(audio_cache.partial_path("stale"))
+            .unwrap()
+            .set_modified(std::time::SystemTime::now() - STALE_PARTIAL_DOWNLOAD_AGE)
+            .unwrap();
+
+        audio_cache.remove_stale_partial_downloads().unwrap();
+
+        assert!(audio_cache.partial_path("fresh").exists());
+        assert!(!audio_cache.partial_path("stale").exists());
+
+        std::fs::remove_dir_all(&audio_cache.dir).unwrap();
+    }
+}
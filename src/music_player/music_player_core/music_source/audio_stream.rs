@@ -0,0 +1,200 @@
+#[derive(Debug, Clone)]
+pub struct AudioStreamPreferences {
+    pub codec: String,
+    pub max_bitrate_kbps: u64,
+    pub language: String,
+}
+
+impl Default for AudioStreamPreferences {
+    fn default() -> Self {
+        Self {
+            codec: "any".to_string(),
+            max_bitrate_kbps: 0,
+            language: "".to_string(),
+        }
+    }
+}
+
+fn get_str<'a>(stream: &'a serde_json::Value, key: &str) -> &'a str {
+    stream
+        .get(key)
+        .and_then(|value| value.as_str())
+        .unwrap_or_default()
+}
+
+fn get_bitrate(stream: &serde_json::Value) -> u64 {
+    stream
+        .get("bitrate")
+        .and_then(|bitrate| bitrate.as_u64())
+        .unwrap_or_default()
+}
+
+fn matches_codec(stream: &serde_json::Value, codec: &str) -> bool {
+    let stream_codec = get_str(stream, "codec").to_lowercase();
+    let stream_format = get_str(stream, "format").to_lowercase();
+
+    match codec {
+        "opus" | "webm" => stream_codec.contains("opus") || stream_format.contains("webm"),
+        "aac" | "m4a" => stream_codec.contains("mp4a") || stream_format.contains("m4a"),
+        _ => true,
+    }
+}
+
+fn matches_language(stream: &serde_json::Value, language: &str) -> bool {
+    let locale = get_str(stream, "audioTrackLocale").to_lowercase();
+    let track_id = get_str(stream, "audioTrackId").to_lowercase();
+
+    locale.starts_with(language) || track_id.starts_with(language)
+}
+
+fn narrow<F>(streams: Vec<&serde_json::Value>, predicate: F) -> Vec<&serde_json::Value>
+where
+    F: Fn(&serde_json::Value) -> bool,
+{
+    let narrowed: Vec<&serde_json::Value> = streams
+        .iter()
+        .copied()
+        .filter(|stream| predicate(stream))
+        .collect();
+
+    if narrowed.is_empty() {
+        streams
+    } else {
+        narrowed
+    }
+}
+
+pub fn select_audio_stream<'a>(
+    audio_streams: &'a [serde_json::Value],
+    preferences: &AudioStreamPreferences,
+) -> Option<&'a serde_json::Value> {
+    let streams: Vec<&serde_json::Value> = audio_streams
+        .iter()
+        .filter(|stream| !get_str(stream, "url").is_empty())
+        .collect();
+
+    let language = preferences.language.to_lowercase();
+    let streams = if language.is_empty() {
+        streams
+    } else {
+        narrow(streams, |stream| matches_language(stream, &language))
+    };
+
+    let codec = preferences.codec.to_lowercase();
+    let streams = narrow(streams, |stream| matches_codec(stream, &codec));
+
+    if preferences.max_bitrate_kbps != 0 {
+        let max_bitrate = preferences.max_bitrate_kbps * 1000;
+        let within_limit = streams
+            .iter()
+            .copied()
+            .filter(|stream| get_bitrate(stream) <= max_bitrate)
+            .max_by_key(|stream| get_bitrate(stream));
+
+        if within_limit.is_some() {
+            return within_limit;
+        }
+        return streams.into_iter().min_by_key(|stream| get_bitrate(stream));
+    }
+
+    streams.into_iter().max_by_key(|stream| get_bitrate(stream))
+}
+
+pub fn describe_audio_stream(audio_stream: &serde_json::Value) -> String {
+    format!(
+        "{} {} {} kbps",
+        get_str(audio_stream, "format"),
+        get_str(audio_stream, "codec"),
+        get_bitrate(audio_stream) / 1000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stream(url: &str, codec: &str, bitrate_kbps: u64, locale: &str) -> serde_json::Value {
+        let format = if codec == "opus" { "WEBMA_OPUS" } else { "M4A" };
+
+        serde_json::json!({
+            "url": url,
+            "codec": codec,
+            "format": format,
+            "bitrate": bitrate_kbps * 1000,
+            "audioTrackLocale": locale,
+        })
+    }
+
+    fn preferences(codec: &str, max_bitrate_kbps: u64, language: &str) -> AudioStreamPreferences {
+        AudioStreamPreferences {
+            codec: codec.to_string(),
+            max_bitrate_kbps,
+            language: language.to_string(),
+        }
+    }
+
+    fn selected_url(streams: &[serde_json::Value], preferences: &AudioStreamPreferences) -> String {
+        let stream = select_audio_stream(streams, preferences).unwrap();
+
+        get_str(stream, "url").to_string()
+    }
+
+    #[test]
+    fn codec_preference_narrows_streams() {
+        let streams = [
+            stream("opus_low", "opus", 64, ""),
+            stream("aac", "mp4a.40.2", 128, ""),
+            stream("opus", "opus", 160, ""),
+            stream("", "opus", 320, ""),
+        ];
+
+        assert_eq!(selected_url(&streams, &preferences("any", 0, "")), "opus");
+        assert_eq!(selected_url(&streams, &preferences("AAC", 0, "")), "aac");
+        assert_eq!(selected_url(&streams, &preferences("webm", 0, "")), "opus");
+        assert_eq!(
+            selected_url(&streams[..2], &preferences("m4a", 0, "")),
+            "aac"
+        );
+        assert_eq!(
+            selected_url(&streams[..1], &preferences("aac", 0, "")),
+            "opus_low"
+        );
+    }
+
+    #[test]
+    fn max_bitrate_caps_selection() {
+        let streams = [
+            stream("low", "opus", 64, ""),
+            stream("mid", "opus", 128, ""),
+            stream("high", "opus", 160, ""),
+        ];
+
+        assert_eq!(selected_url(&streams, &preferences("any", 130, "")), "mid");
+        assert_eq!(selected_url(&streams, &preferences("any", 64, "")), "low");
+        assert_eq!(selected_url(&streams, &preferences("any", 32, "")), "low");
+    }
+
+    #[test]
+    fn language_preference_matches_track() {
+        let streams = [
+            stream("en", "opus", 160, "en-US"),
+            stream("de", "opus", 128, "de-DE"),
+            stream("de_aac", "mp4a.40.2", 96, "de-DE"),
+        ];
+
+        assert_eq!(selected_url(&streams, &preferences("any", 0, "DE")), "de");
+        assert_eq!(
+            selected_url(&streams, &preferences("aac", 0, "de")),
+            "de_aac"
+        );
+        assert_eq!(selected_url(&streams, &preferences("any", 0, "fr")), "en");
+        assert_eq!(selected_url(&streams, &preferences("any", 0, "")), "en");
+    }
+
+    #[test]
+    fn streams_without_url_are_ignored() {
+        let streams = [stream("", "opus", 160, "")];
+
+        assert!(select_audio_stream(&streams, &preferences("any", 0, "")).is_none());
+    }
+}
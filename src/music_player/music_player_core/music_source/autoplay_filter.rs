@@ -0,0 +1,156 @@
+use crate::music_player::Error;
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct AutoplayFilterConfig {
+    pub blocked_uploaders: Vec<String>,
+    pub blocked_channel_ids: Vec<String>,
+    pub blocked_title_patterns: Vec<String>,
+    pub allowed_uploaders: Vec<String>,
+    pub allowed_channel_ids: Vec<String>,
+    pub allowed_title_patterns: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AutoplayFilter {
+    blocked_uploaders: Vec<String>,
+    blocked_channel_ids: Vec<String>,
+    blocked_title_patterns: Vec<regex::Regex>,
+    allowed_uploaders: Vec<String>,
+    allowed_channel_ids: Vec<String>,
+    allowed_title_patterns: Vec<regex::Regex>,
+}
+
+impl AutoplayFilter {
+    pub fn new(config: &AutoplayFilterConfig) -> Result<Self, Error> {
+        Ok(Self {
+            blocked_uploaders: Self::lowercase(&config.blocked_uploaders),
+            blocked_channel_ids: config.blocked_channel_ids.clone(),
+            blocked_title_patterns: Self::compile_patterns(&config.blocked_title_patterns)?,
+            allowed_uploaders: Self::lowercase(&config.allowed_uploaders),
+            allowed_channel_ids: config.allowed_channel_ids.clone(),
+            allowed_title_patterns: Self::compile_patterns(&config.allowed_title_patterns)?,
+        })
+    }
+
+    fn lowercase(names: &[String]) -> Vec<String> {
+        names.iter().map(|name| name.to_lowercase()).collect()
+    }
+
+    fn compile_patterns(patterns: &[String]) -> Result<Vec<regex::Regex>, Error> {
+        patterns
+            .iter()
+            .map(|pattern| {
+                regex::RegexBuilder::new(pattern)
+                    .case_insensitive(true)
+                    .build()
+                    .map_err(|err| {
+                        Error::InvalidConfig(format!(
+                            "Invalid autoplay filter pattern {pattern:?}: {err}"
+                        ))
+                    })
+            })
+            .collect()
+    }
+
+    pub fn channel_id_from_url(uploader_url: &str) -> &str {
+        uploader_url.rsplit('/').next().unwrap_or_default()
+    }
+
+    pub fn is_allowed(&self, uploader: &str, channel_id: &str, title: &str) -> bool {
+        let uploader = uploader.to_lowercase();
+
+        if self.blocked_uploaders.contains(&uploader)
+            || self.blocked_channel_ids.iter().any(|id| id == channel_id)
+            || self
+                .blocked_title_patterns
+                .iter()
+                .any(|regex| regex.is_match(title))
+        {
+            return false;
+        }
+
+        let has_allowlist = !self.allowed_uploaders.is_empty()
+            || !self.allowed_channel_ids.is_empty()
+            || !self.allowed_title_patterns.is_empty();
+        if !has_allowlist {
+            return true;
+        }
+
+        self.allowed_uploaders.contains(&uploader)
+            || self.allowed_channel_ids.iter().any(|id| id == channel_id)
+            || self
+                .allowed_title_patterns
+                .iter()
+                .any(|regex| regex.is_match(title))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patterns(patterns: &[&str]) -> Vec<String> {
+        patterns.iter().map(|pattern| pattern.to_string()).collect()
+    }
+
+    #[test]
+    fn empty_filter_allows_everything() {
+        let autoplay_filter = AutoplayFilter::new(&AutoplayFilterConfig::default()).unwrap();
+
+        assert!(autoplay_filter.is_allowed("Uploader", "UC123", "Title"));
+    }
+
+    #[test]
+    fn blocklist_rejects_matches() {
+        let autoplay_filter = AutoplayFilter::new(&AutoplayFilterConfig {
+            blocked_uploaders: patterns(&["Some Uploader"]),
+            blocked_channel_ids: patterns(&["UC123"]),
+            blocked_title_patterns: patterns(&[r"\bnightcore\b"]),
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert!(!autoplay_filter.is_allowed("some uploader", "UC999", "Title"));
+        assert!(!autoplay_filter.is_allowed("Other", "UC123", "Title"));
+        assert!(!autoplay_filter.is_allowed("Other", "UC999", "Title (Nightcore)"));
+        assert!(autoplay_filter.is_allowed("Other", "UC999", "Title"));
+    }
+
+    #[test]
+    fn allowlist_requires_a_match() {
+        let autoplay_filter = AutoplayFilter::new(&AutoplayFilterConfig {
+            allowed_uploaders: patterns(&["Artist"]),
+            allowed_title_patterns: patterns(&["official audio"]),
+            blocked_title_patterns: patterns(&["live"]),
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert!(autoplay_filter.is_allowed("ARTIST", "UC1", "Song"));
+        assert!(autoplay_filter.is_allowed("Other", "UC1", "Song (Official Audio)"));
+        assert!(!autoplay_filter.is_allowed("Artist", "UC1", "Song (Live)"));
+        assert!(!autoplay_filter.is_allowed("Other", "UC1", "Song"));
+    }
+
+    #[test]
+    fn invalid_pattern_is_a_config_error() {
+        let result = AutoplayFilter::new(&AutoplayFilterConfig {
+            allowed_title_patterns: patterns(&["valid", "(unclosed"]),
+            ..Default::default()
+        });
+
+        match result {
+            Err(Error::InvalidConfig(msg)) => assert!(msg.contains("(unclosed")),
+            result => panic!("{result:?}"),
+        }
+    }
+
+    #[test]
+    fn channel_id_from_url() {
+        assert_eq!(
+            AutoplayFilter::channel_id_from_url("/channel/UC123"),
+            "UC123"
+        );
+    }
+}
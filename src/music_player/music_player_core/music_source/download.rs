@@ -0,0 +1,209 @@
+use super::Remote;
+use crate::music_player::Error;
+use crate::utils;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone)]
+pub struct DownloadConfig {
+    pub dir: String,
+    pub filename_template: String,
+    pub ffmpeg_path: String,
+}
+
+#[derive(Debug)]
+pub struct DownloadedFile {
+    pub path: PathBuf,
+    pub tag_error: Option<Error>,
+}
+
+pub fn render_filename(template: &str, source: &Remote) -> String {
+    let filename = template
+        .replace("{title}", &source.title)
+        .replace("{uploader}", &source.uploader)
+        .replace("{video_id}", &source.video_id);
+
+    let filename: String = filename
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    let filename = filename.trim().trim_matches('.');
+
+    if filename.is_empty() {
+        source.video_id.to_string()
+    } else {
+        filename.to_string()
+    }
+}
+
+// Existing files are kept, a numbered suffix is added instead
+fn unique_filename(dir: &Path, filename: &str, extensions: &[&str]) -> String {
+    let mut unique_filename = filename.to_string();
+    let mut i = 1;
+    while extensions
+        .iter()
+        .any(|extension| dir.join(format!("{unique_filename}.{extension}")).exists())
+    {
+        unique_filename = format!("{filename} ({i})");
+        i += 1;
+    }
+
+    unique_filename
+}
+
+fn extensions(source: &Remote) -> (&'static str, &'static str) {
+    let audio_format = source.audio_format.to_lowercase();
+
+    if audio_format.contains("m4a") || audio_format.contains("mp4a") {
+        ("m4a", "m4a")
+    } else {
+        ("webm", "opus")
+    }
+}
+
+pub fn download(config: &DownloadConfig, source: &Remote) -> Result<DownloadedFile, Error> {
+    std::fs::create_dir_all(&config.dir)?;
+
+    let dir = Path::new(&config.dir);
+    let (raw_extension, tagged_extension) = extensions(source);
+    let partial_extension = format!("{raw_extension}.part");
+    let filename = unique_filename(
+        dir,
+        &render_filename(&config.filename_template, source),
+        &[raw_extension, tagged_extension, &partial_extension],
+    );
+    let partial_path = dir.join(format!("{filename}.{partial_extension}"));
+
+    let mut partial_file = std::fs::File::options()
+        .write(true)
+        .create_new(true)
+        .open(&partial_path)?;
+    let result = if Path::new(&source.audio_stream_url).is_file() {
+        std::fs::File::open(&source.audio_stream_url)
+            .and_then(|mut cached_file| std::io::copy(&mut cached_file, &mut partial_file))
+            .map_err(Error::from)
+    } else {
+        utils::reqwest_download(&source.audio_stream_url, &mut partial_file)
+    };
+    drop(partial_file);
+    if let Err(err) = result {
+        std::fs::remove_file(&partial_path)?;
+        return Err(err);
+    }
+
+    let tagged_path = dir.join(format!("{filename}.{tagged_extension}"));
+    let result = tag(config, source, &partial_path, &tagged_path);
+    log::info!("download::download -> {:?}::{:?}", tagged_path, result);
+
+    if let Err(err) = result {
+        let raw_path = dir.join(format!("{filename}.{raw_extension}"));
+        std::fs::rename(&partial_path, &raw_path)?;
+
+        return Ok(DownloadedFile {
+            path: raw_path,
+            tag_error: Some(err),
+        });
+    }
+
+    std::fs::remove_file(&partial_path)?;
+    Ok(DownloadedFile {
+        path: tagged_path,
+        tag_error: None,
+    })
+}
+
+fn tag(config: &DownloadConfig, source: &Remote, input: &Path, output: &Path) -> Result<(), Error> {
+    if config.ffmpeg_path.is_empty() {
+        return Err(Error::OtherError("ffmpeg_path is not set".to_string()));
+    }
+
+    let source_url = format!("https://www.youtube.com/watch?v={}", source.video_id);
+    let status = std::process::Command::new(&config.ffmpeg_path)
+        .args(["-y", "-loglevel", "error", "-i"])
+        .arg(input)
+        .args(["-map", "0:a", "-c", "copy"])
+        .arg("-metadata")
+        .arg(format!("title={}", source.title))
+        .arg("-metadata")
+        .arg(format!("artist={}", source.uploader))
+        .arg("-metadata")
+        .arg(format!("comment={source_url}"))
+        .arg("-metadata")
+        .arg(format!("purl={source_url}"))
+        .arg(output)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .map_err(|err| match err.kind() {
+            std::io::ErrorKind::NotFound => {
+                Error::OtherError(format!("ffmpeg not found: {}", config.ffmpeg_path))
+            }
+            _ => err.into(),
+        })?;
+
+    if !status.success() {
+        let _ = std::fs::remove_file(output);
+        return Err(Error::OtherError(format!("ffmpeg exited with {status}")));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn remote(title: &str, uploader: &str) -> Remote {
+        let mut remote = Remote::new("https://www.youtube.com/watch?v=dQw4w9WgXcQ").unwrap();
+        remote.title = title.to_string();
+        remote.uploader = uploader.to_string();
+
+        remote
+    }
+
+    #[test]
+    fn render_filename_fills_placeholders() {
+        let source = remote("Song", "Artist");
+
+        assert_eq!(
+            render_filename("{uploader} - {title} [{video_id}]", &source),
+            "Artist - Song [dQw4w9WgXcQ]"
+        );
+    }
+
+    #[test]
+    fn render_filename_sanitizes_path_characters() {
+        let source = remote("AC/DC: Back in Black?", "Artist\n");
+
+        assert_eq!(
+            render_filename("{uploader} - {title}", &source),
+            "Artist_ - AC_DC_ Back in Black_"
+        );
+        assert_eq!(render_filename("../{title}", &remote("x", "")), "_x");
+    }
+
+    #[test]
+    fn render_filename_falls_back_to_video_id() {
+        assert_eq!(
+            render_filename(" {title}. ", &remote("", "")),
+            "dQw4w9WgXcQ"
+        );
+    }
+
+    #[test]
+    fn unique_filename_keeps_existing_files() {
+        let dir = crate::utils::temp_dir("download");
+
+        assert_eq!(unique_filename(&dir, "Song", &["m4a"]), "Song");
+
+        std::fs::write(dir.join("Song.m4a"), b"").unwrap();
+        std::fs::write(dir.join("Song (1).opus"), b"").unwrap();
+        assert_eq!(unique_filename(&dir, "Song", &["m4a", "opus"]), "Song (2)");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,177 @@
+use crate::music_player::Error;
+use crate::utils;
+
+#[derive(Debug, Clone, Default)]
+pub struct Lyrics {
+    pub timed: Vec<(f64, String)>,
+    pub plain: Vec<String>,
+}
+
+impl Lyrics {
+    pub fn is_empty(&self) -> bool {
+        self.timed.is_empty() && self.plain.is_empty()
+    }
+}
+
+pub fn clean_title(title: &str) -> String {
+    let mut cleaned = String::new();
+    let mut depth = 0;
+
+    for c in title.chars() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth = (depth - 1).max(0),
+            c if depth == 0 => cleaned.push(c),
+            _ => (),
+        }
+    }
+
+    cleaned.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+fn parse_timestamp(timestamp: &str) -> Option<f64> {
+    let (minutes, seconds) = timestamp.split_once(':')?;
+    let minutes: f64 = minutes.parse().ok()?;
+    let seconds: f64 = seconds.parse().ok()?;
+
+    Some(minutes * 60.0 + seconds)
+}
+
+pub fn parse_lrc(lrc: &str) -> Vec<(f64, String)> {
+    let mut timed = Vec::new();
+
+    for line in lrc.lines() {
+        let mut rest = line.trim();
+        let mut timestamps = Vec::new();
+        while let Some(tag) = rest.strip_prefix('[') {
+            let (timestamp, text) = match tag.split_once(']') {
+                Some(split) => split,
+                None => break,
+            };
+            if let Some(time) = parse_timestamp(timestamp) {
+                timestamps.push(time);
+            }
+            rest = text;
+        }
+
+        for time in timestamps {
+            timed.push((time, rest.trim().to_string()));
+        }
+    }
+
+    timed.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+    timed
+}
+
+pub fn fetch_lyrics(api: &str, title: &str, length: u64) -> Result<Lyrics, Error> {
+    let query = clean_title(title);
+    let request_url = format!(
+        "{}/api/search?q={}",
+        api.trim_end_matches('/'),
+        urlencoding::encode(&query)
+    );
+
+    let response: serde_json::Value = utils::reqwest_get(&request_url)?.json()?;
+    let results = response
+        .as_array()
+        .ok_or_else(|| Error::OtherError(format!("{:?}", response.to_string())))?;
+
+    select_lyrics(results, length)
+        .ok_or_else(|| Error::OtherError(format!("No lyrics found for: {query}")))
+}
+
+// Synced lyrics are only used when the duration matches, the timestamps
+// of another recording would drift
+fn select_lyrics(results: &[serde_json::Value], length: u64) -> Option<Lyrics> {
+    let get_str = |result: &serde_json::Value, key: &str| {
+        result
+            .get(key)
+            .and_then(|value| value.as_str())
+            .filter(|value| !value.is_empty())
+            .map(|value| value.to_string())
+    };
+    let duration_matches = |result: &serde_json::Value| {
+        let duration = result
+            .get("duration")
+            .and_then(|duration| duration.as_f64())
+            .unwrap_or_default();
+        length == 0 || (duration - length as f64).abs() <= 5.0
+    };
+
+    let synced = results
+        .iter()
+        .filter(|result| duration_matches(result))
+        .find_map(|result| get_str(result, "syncedLyrics"));
+    if let Some(synced) = synced {
+        return Some(Lyrics {
+            timed: parse_lrc(&synced),
+            plain: Vec::new(),
+        });
+    }
+
+    let plain = results
+        .iter()
+        .find_map(|result| get_str(result, "plainLyrics"))?;
+
+    Some(Lyrics {
+        timed: Vec::new(),
+        plain: plain.lines().map(|line| line.to_string()).collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clean_title_strips_brackets() {
+        assert_eq!(
+            clean_title("Artist - Song (Official Video) [HD]"),
+            "Artist - Song"
+        );
+        assert_eq!(clean_title(r#"Song "Live" [HD]"#), r#"Song "Live""#);
+        assert_eq!(clean_title("Song (feat. (Someone))  Remix"), "Song Remix");
+        assert_eq!(clean_title("Unbalanced) Title"), "Unbalanced Title");
+    }
+
+    #[test]
+    fn parse_lrc_sorts_lines_and_expands_repeated_timestamps() {
+        let lrc =
+            "[ar:Artist]\n[00:12.50]Second\n[00:01.00][01:02.25] Chorus \n[00:05.00]\nno timestamp";
+
+        assert_eq!(
+            parse_lrc(lrc),
+            vec![
+                (1.0, "Chorus".to_string()),
+                (5.0, "".to_string()),
+                (12.5, "Second".to_string()),
+                (62.25, "Chorus".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn select_lyrics_requires_matching_duration_for_synced() {
+        let results = vec![
+            serde_json::json!({
+                "duration": 300.0,
+                "syncedLyrics": "[00:01.00]Other recording",
+                "plainLyrics": "Other recording"
+            }),
+            serde_json::json!({
+                "duration": 212.0,
+                "syncedLyrics": "[00:01.00]Matching",
+                "plainLyrics": "Matching"
+            }),
+        ];
+
+        let lyrics = select_lyrics(&results, 213).unwrap();
+        assert_eq!(lyrics.timed, vec![(1.0, "Matching".to_string())]);
+
+        let lyrics = select_lyrics(&results[..1], 213).unwrap();
+        assert!(lyrics.timed.is_empty());
+        assert_eq!(lyrics.plain, vec!["Other recording"]);
+
+        assert!(select_lyrics(&[], 213).is_none());
+    }
+}
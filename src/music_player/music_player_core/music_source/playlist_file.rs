@@ -0,0 +1,429 @@
+use crate::music_player::music_player_core::music_source::{Source, Stream};
+use crate::music_player::Error;
+use std::fmt::Write;
+use std::path::Path;
+
+const PLAYLIST_FILE_EXTENSIONS: [&str; 3] = ["m3u", "m3u8", "pls"];
+
+pub fn is_valid_playlist_file_path(path: &str) -> bool {
+    let path = Path::new(path);
+
+    path.is_file() && get_playlist_file_extension(path).is_some()
+}
+
+pub fn has_playlist_file_extension(path: &str) -> bool {
+    get_playlist_file_extension(Path::new(path)).is_some()
+}
+
+fn get_playlist_file_extension(path: &Path) -> Option<String> {
+    let extension = path.extension()?.to_str()?.to_lowercase();
+
+    if PLAYLIST_FILE_EXTENSIONS.contains(&extension.as_str()) {
+        Some(extension)
+    } else {
+        None
+    }
+}
+
+pub fn playlist_file_to_source_vec(path: &str) -> Result<Vec<Source>, Error> {
+    log::info!("playlist_file::playlist_file_to_source_vec -> {:?}", path);
+
+    let playlist_path = Path::new(path);
+    let content = std::fs::read(playlist_path)?;
+    let content = String::from_utf8_lossy(&content);
+    let base_dir = playlist_path.parent().unwrap_or(Path::new(""));
+
+    let entries = match get_playlist_file_extension(playlist_path).as_deref() {
+        Some("pls") => parse_pls(&content),
+        _ => parse_m3u(&content),
+    };
+
+    let mut playlist = Vec::new();
+    for entry in entries {
+        match entry_into_source(entry, base_dir) {
+            Ok(source) => playlist.push(source),
+            Err(err) => log::info!(
+                "playlist_file::playlist_file_to_source_vec -> Skipping: {:?}",
+                err
+            ),
+        }
+    }
+
+    if playlist.is_empty() {
+        return Err(Error::InvalidPlaylistFile(format!(
+            "No playable entries found in: {path}"
+        )));
+    }
+
+    Ok(playlist)
+}
+
+struct PlaylistEntry {
+    location: String,
+    title: Option<String>,
+    length: Option<u64>,
+}
+
+fn entry_into_source(entry: PlaylistEntry, base_dir: &Path) -> Result<Source, Error> {
+    let location = entry.location.trim();
+
+    let mut source = if location.contains("watch?v=") {
+        Source::new_remote(location)?
+    } else if Stream::is_valid_url(location) {
+        Source::new_stream(location)?
+    } else {
+        let location = location.strip_prefix("file://").unwrap_or(location);
+        let location = base_dir.join(location);
+
+        Source::new_local(&location.to_string_lossy())?
+    };
+
+    source.set_title_length(entry.title, entry.length);
+
+    Ok(source)
+}
+
+fn parse_m3u(content: &str) -> Vec<PlaylistEntry> {
+    let mut entries = Vec::new();
+    let mut title = None;
+    let mut length = None;
+
+    for line in content.lines() {
+        let line = line.trim().trim_start_matches('\u{feff}');
+
+        if let Some(extinf) = line.strip_prefix("#EXTINF:") {
+            let mut extinf = extinf.splitn(2, ',');
+            length = extinf
+                .next()
+                .and_then(|length| length.split_whitespace().next())
+                .and_then(|length| length.parse::<i64>().ok())
+                .and_then(|length| u64::try_from(length).ok());
+            title = extinf
+                .next()
+                .map(|title| title.trim().to_string())
+                .filter(|title| !title.is_empty());
+        } else if line.is_empty() || line.starts_with('#') {
+            continue;
+        } else {
+            entries.push(PlaylistEntry {
+                location: line.to_string(),
+                title: title.take(),
+                length: length.take(),
+            });
+        }
+    }
+
+    entries
+}
+
+fn parse_pls(content: &str) -> Vec<PlaylistEntry> {
+    let mut entries: Vec<(usize, PlaylistEntry)> = Vec::new();
+
+    for line in content.lines() {
+        let line = line.trim();
+        let mut key_value = line.splitn(2, '=');
+        let (key, value) = match (key_value.next(), key_value.next()) {
+            (Some(key), Some(value)) => (key.trim().to_lowercase(), value.trim()),
+            _ => continue,
+        };
+
+        let (field, number) = match key.find(|c: char| c.is_ascii_digit()) {
+            Some(i) => (&key[..i], key[i..].parse::<usize>()),
+            None => continue,
+        };
+        let number = match number {
+            Ok(number) => number,
+            Err(_) => continue,
+        };
+
+        let entry = match entries.iter_mut().find(|(n, _)| *n == number) {
+            Some((_, entry)) => entry,
+            None => {
+                entries.push((
+                    number,
+                    PlaylistEntry {
+                        location: "".to_string(),
+                        title: None,
+                        length: None,
+                    },
+                ));
+                &mut entries.last_mut().unwrap().1
+            }
+        };
+
+        match field {
+            "file" => entry.location = value.to_string(),
+            "title" => entry.title = Some(value.to_string()).filter(|title| !title.is_empty()),
+            "length" => entry.length = value.parse::<u64>().ok(),
+            _ => (),
+        }
+    }
+
+    entries.sort_by_key(|(number, _)| *number);
+
+    entries
+        .into_iter()
+        .map(|(_, entry)| entry)
+        .filter(|entry| !entry.location.is_empty())
+        .collect()
+}
+
+#[derive(Debug)]
+pub enum ExportFormat {
+    M3u8,
+    Json,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::M3u8 => "m3u8",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
+pub fn export_queue(
+    to_play: &[Source],
+    to_play_index: usize,
+    played_video_ids: &[String],
+    format: &ExportFormat,
+    path: &str,
+) -> Result<String, Error> {
+    // An empty path or a directory gets a timestamped file name
+    let path = if path.is_empty() || std::path::Path::new(path).is_dir() {
+        let file_name = format!(
+            "rustunes_export_{}.{}",
+            chrono::Local::now().format("%Y%m%d_%H%M%S"),
+            format.extension()
+        );
+        std::path::Path::new(path)
+            .join(file_name)
+            .to_string_lossy()
+            .to_string()
+    } else {
+        path.to_string()
+    };
+
+    log::info!("playlist_file::export_queue -> {:?}", path);
+
+    let content = match format {
+        ExportFormat::M3u8 => queue_into_m3u8(to_play, played_video_ids),
+        ExportFormat::Json => queue_into_json(to_play, to_play_index, played_video_ids)?,
+    };
+
+    std::fs::write(&path, content)?;
+
+    Ok(path)
+}
+
+fn queue_into_m3u8(to_play: &[Source], played_video_ids: &[String]) -> String {
+    let mut m3u8 = String::new();
+
+    writeln!(m3u8, "#EXTM3U").unwrap();
+    for source in to_play {
+        let title = source.get_title();
+        if !title.is_empty() {
+            let length = match source.get_length() {
+                0 => -1,
+                length => length as i64,
+            };
+            writeln!(m3u8, "#EXTINF:{length},{title}").unwrap();
+        }
+        writeln!(m3u8, "{}", source.to_url()).unwrap();
+    }
+
+    for video_id in get_played_video_ids_not_in_queue(to_play, played_video_ids) {
+        writeln!(m3u8, "https://www.youtube.com/watch?v={video_id}").unwrap();
+    }
+
+    m3u8
+}
+
+fn queue_into_json(
+    to_play: &[Source],
+    to_play_index: usize,
+    played_video_ids: &[String],
+) -> Result<String, Error> {
+    let queue: Vec<serde_json::Value> = to_play
+        .iter()
+        .map(|source| {
+            let source_type = match source {
+                Source::Remote(_) => "remote",
+                Source::Local(_) => "local",
+                Source::Stream(_) => "stream",
+            };
+
+            serde_json::json!({
+                "type": source_type,
+                "url": source.to_url(),
+                "title": source.get_title(),
+                "length": source.get_length(),
+            })
+        })
+        .collect();
+
+    let json = serde_json::json!({
+        "queue": queue,
+        "current_index": to_play_index.saturating_sub(1),
+        "played_video_ids": played_video_ids,
+    });
+
+    Ok(serde_json::to_string_pretty(&json)?)
+}
+
+fn get_played_video_ids_not_in_queue<'a>(
+    to_play: &[Source],
+    played_video_ids: &'a [String],
+) -> Vec<&'a String> {
+    played_video_ids
+        .iter()
+        .filter(|video_id| {
+            !to_play.iter().any(|source| match source {
+                Source::Remote(remote_src) => &&remote_src.video_id == video_id,
+                _ => false,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_playlist(name: &str, content: &str) -> String {
+        let dir = crate::utils::temp_dir(&format!("playlist_file_{name}"));
+        std::fs::create_dir_all(dir.join("music")).unwrap();
+        std::fs::write(dir.join("music").join("song.mp3"), b"").unwrap();
+
+        let path = dir.join(name);
+        std::fs::write(&path, content).unwrap();
+
+        path.to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn parse_m3u_reads_extinf() {
+        let entries = parse_m3u(
+            "\u{feff}#EXTM3U\n\
+             #EXTINF:213,Artist - Song\n\
+             song.mp3\n\
+             \n\
+             #EXTINF:-1 tvg-id=\"x\",\n\
+             http://radio.example/stream\n\
+             other.mp3\n",
+        );
+
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].location, "song.mp3");
+        assert_eq!(entries[0].title.as_deref(), Some("Artist - Song"));
+        assert_eq!(entries[0].length, Some(213));
+        assert_eq!(entries[1].title, None);
+        assert_eq!(entries[1].length, None);
+        assert_eq!(entries[2].title, None);
+    }
+
+    #[test]
+    fn parse_pls_pairs_fields_by_number() {
+        let entries = parse_pls(
+            "[playlist]\n\
+             Title2=Second\n\
+             File2=second.mp3\n\
+             File1=first.mp3\n\
+             Title1=First\n\
+             Length1=-1\n\
+             Length2=120\n\
+             Title3=No file\n\
+             NumberOfEntries=3\n",
+        );
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].location, "first.mp3");
+        assert_eq!(entries[0].title.as_deref(), Some("First"));
+        assert_eq!(entries[0].length, None);
+        assert_eq!(entries[1].location, "second.mp3");
+        assert_eq!(entries[1].title.as_deref(), Some("Second"));
+        assert_eq!(entries[1].length, Some(120));
+    }
+
+    #[test]
+    fn m3u8_entries_become_each_source_kind() {
+        let path = temp_playlist(
+            "kinds.m3u8",
+            "#EXTM3U\n\
+             #EXTINF:213,Song\n\
+             music/song.mp3\n\
+             https://www.youtube.com/watch?v=dQw4w9WgXcQ\n\
+             https://radio.example/stream\n\
+             missing.mp3\n",
+        );
+
+        let sources = playlist_file_to_source_vec(&path).unwrap();
+
+        assert_eq!(sources.len(), 3);
+        match &sources[0] {
+            Source::Local(local_src) => {
+                let expected = Path::new(&path).parent().unwrap().join("music/song.mp3");
+                assert_eq!(Path::new(&local_src.path), expected);
+                assert_eq!(local_src.title, "Song");
+                assert_eq!(local_src.length, 213);
+            }
+            source => panic!("{:?}", source),
+        }
+        assert_eq!(
+            sources[1].get_remote_source().unwrap().video_id,
+            "dQw4w9WgXcQ"
+        );
+        assert!(matches!(&sources[2], Source::Stream(stream_src)
+            if stream_src.url == "https://radio.example/stream"));
+
+        std::fs::remove_dir_all(Path::new(&path).parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn m3u_and_pls_resolve_relative_paths() {
+        let m3u = temp_playlist("relative.m3u", "music/song.mp3\n");
+        let pls = temp_playlist(
+            "relative.pls",
+            "[playlist]\nFile1=file://music/song.mp3\nTitle1=Song\n",
+        );
+
+        for path in [m3u, pls] {
+            let sources = playlist_file_to_source_vec(&path).unwrap();
+
+            assert_eq!(sources.len(), 1);
+            assert!(matches!(&sources[0], Source::Local(local_src)
+                if Path::new(&local_src.path).is_file()));
+
+            std::fs::remove_dir_all(Path::new(&path).parent().unwrap()).unwrap();
+        }
+    }
+
+    #[test]
+    fn export_writes_titles_as_stored() {
+        let mut source = Source::new_remote("https://www.youtube.com/watch?v=dQw4w9WgXcQ").unwrap();
+        source.set_title_length(Some(r#"Song "Live""#.to_string()), Some(213));
+        let to_play = vec![source];
+
+        assert_eq!(
+            queue_into_m3u8(&to_play, &[]),
+            "#EXTM3U\n#EXTINF:213,Song \"Live\"\nhttps://www.youtube.com/watch?v=dQw4w9WgXcQ\n"
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&queue_into_json(&to_play, 1, &[]).unwrap()).unwrap();
+        assert_eq!(json["queue"][0]["title"], r#"Song "Live""#);
+    }
+
+    #[test]
+    fn playlist_without_playable_entries_is_rejected() {
+        let path = temp_playlist("empty.m3u", "#EXTM3U\nmissing.mp3\n");
+
+        assert!(matches!(
+            playlist_file_to_source_vec(&path),
+            Err(Error::InvalidPlaylistFile(_))
+        ));
+
+        std::fs::remove_dir_all(Path::new(&path).parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,202 @@
+use rand::Rng;
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct RecommendationConfig {
+    pub same_uploader_penalty: f64,
+    pub diversity_window: usize,
+    pub diversity_penalty: f64,
+    pub recency_decay: f64,
+    pub seeds_count: usize,
+    pub randomness: f64,
+    pub liked_seed_boost: f64,
+}
+
+impl Default for RecommendationConfig {
+    fn default() -> Self {
+        Self {
+            same_uploader_penalty: 0.5,
+            diversity_window: 10,
+            diversity_penalty: 0.2,
+            recency_decay: 0.8,
+            seeds_count: 5,
+            randomness: 0.2,
+            liked_seed_boost: 2.0,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct RelatedCandidate {
+    pub index: usize,
+    pub uploader: String,
+    pub score: f64,
+}
+
+pub fn seed_weight(recency_rank: usize, listened_ratio: f64, config: &RecommendationConfig) -> f64 {
+    config.recency_decay.powi(recency_rank as i32) * listened_ratio.clamp(0.1, 1.0)
+}
+
+pub fn pick_seed(seeds: &[(String, f64)], config: &RecommendationConfig) -> Option<String> {
+    let mut seeds = seeds.to_vec();
+    seeds.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+    seeds.truncate(config.seeds_count.max(1));
+
+    // Each weight is blended with noise, the randomness decides how much the noise counts
+    let randomness = config.randomness.clamp(0.0, 1.0);
+    let max_weight = seeds.first().map_or(0.0, |(_, weight)| *weight);
+    let mut rng = rand::thread_rng();
+    seeds
+        .into_iter()
+        .map(|(video_id, weight)| {
+            let noise = rng.gen::<f64>() * randomness * max_weight;
+            (video_id, weight * (1.0 - randomness) + noise)
+        })
+        .min_by(|(_, a), (_, b)| b.total_cmp(a))
+        .map(|(video_id, _)| video_id)
+}
+
+pub fn score_candidates(
+    candidates: &mut [RelatedCandidate],
+    recent_uploaders: &VecDeque<String>,
+    config: &RecommendationConfig,
+) {
+    let mut rng = rand::thread_rng();
+    let candidates_count = candidates.len() as f64;
+
+    for (i, candidate) in candidates.iter_mut().enumerate() {
+        let mut score = 1.0 - i as f64 / candidates_count;
+
+        if !candidate.uploader.is_empty() {
+            if recent_uploaders.back() == Some(&candidate.uploader) {
+                score -= config.same_uploader_penalty;
+            }
+
+            let occurrences = recent_uploaders
+                .iter()
+                .rev()
+                .take(config.diversity_window)
+                .filter(|uploader| **uploader == candidate.uploader)
+                .count();
+            score -= config.diversity_penalty * occurrences as f64;
+        }
+
+        if config.randomness > 0.0 {
+            score += config.randomness * rng.gen::<f64>();
+        }
+
+        candidate.score = score;
+    }
+
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> RecommendationConfig {
+        RecommendationConfig {
+            randomness: 0.0,
+            ..RecommendationConfig::default()
+        }
+    }
+
+    fn candidates(uploaders: &[&str]) -> Vec<RelatedCandidate> {
+        uploaders
+            .iter()
+            .enumerate()
+            .map(|(index, uploader)| RelatedCandidate {
+                index,
+                uploader: uploader.to_string(),
+                score: 0.0,
+            })
+            .collect()
+    }
+
+    fn ranked_uploaders(candidates: &[RelatedCandidate]) -> Vec<&str> {
+        candidates
+            .iter()
+            .map(|candidate| candidate.uploader.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn same_uploader_as_last_track_is_penalized() {
+        let config = RecommendationConfig {
+            same_uploader_penalty: 0.8,
+            diversity_penalty: 0.0,
+            ..config()
+        };
+        let recent_uploaders = VecDeque::from(["b".to_string(), "a".to_string()]);
+        let mut candidates = candidates(&["a", "b", "c"]);
+
+        score_candidates(&mut candidates, &recent_uploaders, &config);
+
+        assert_eq!(ranked_uploaders(&candidates), vec!["b", "c", "a"]);
+        assert!((candidates[2].score - 0.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn diversity_penalty_only_counts_window() {
+        let config = RecommendationConfig {
+            same_uploader_penalty: 0.0,
+            diversity_window: 2,
+            diversity_penalty: 0.4,
+            ..config()
+        };
+        let recent_uploaders: VecDeque<String> = ["a", "a", "b", "c"]
+            .iter()
+            .map(|uploader| uploader.to_string())
+            .collect();
+        let mut candidates = candidates(&["b", "a", "c", ""]);
+
+        score_candidates(&mut candidates, &recent_uploaders, &config);
+
+        assert_eq!(ranked_uploaders(&candidates), vec!["a", "b", "", "c"]);
+        assert!((candidates[1].score - 0.6).abs() < 1e-9);
+    }
+
+    #[test]
+    fn seed_weight_decays_with_recency_and_short_listens() {
+        let config = config();
+
+        assert_eq!(seed_weight(0, 1.0, &config), 1.0);
+        assert!((seed_weight(2, 1.0, &config) - 0.64).abs() < 1e-9);
+        assert!((seed_weight(0, 0.5, &config) - 0.5).abs() < 1e-9);
+        assert!((seed_weight(0, 0.0, &config) - 0.1).abs() < 1e-9);
+        assert!((seed_weight(0, 2.0, &config) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn pick_seed_takes_heaviest_seed_without_randomness() {
+        let config = config();
+        let seeds = vec![
+            ("skipped".to_string(), seed_weight(0, 0.2, &config)),
+            ("listened".to_string(), seed_weight(1, 1.0, &config)),
+            ("older".to_string(), seed_weight(3, 1.0, &config)),
+        ];
+
+        assert_eq!(pick_seed(&seeds, &config).as_deref(), Some("listened"));
+        assert_eq!(pick_seed(&[], &config), None);
+    }
+
+    #[test]
+    fn pick_seed_mostly_takes_heaviest_seed_with_small_randomness() {
+        let config = RecommendationConfig {
+            randomness: 0.1,
+            ..config()
+        };
+        let seeds = vec![
+            ("heaviest".to_string(), 1.0),
+            ("lighter".to_string(), 0.8),
+            ("lightest".to_string(), 0.5),
+        ];
+
+        let heaviest_picks = (0..1000)
+            .filter(|_| pick_seed(&seeds, &config).as_deref() == Some("heaviest"))
+            .count();
+        assert!(heaviest_picks >= 990, "{heaviest_picks}");
+    }
+}
@@ -0,0 +1,154 @@
+use crate::music_player::Error;
+use crate::utils;
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Segment {
+    pub start: f64,
+    pub end: f64,
+    pub category: String,
+}
+
+impl Segment {
+    pub fn contains(&self, time: f64) -> bool {
+        time >= self.start && time < self.end - 0.5
+    }
+}
+
+pub fn fetch_segments(
+    api: &str,
+    video_id: &str,
+    categories: &[String],
+) -> Result<Vec<Segment>, Error> {
+    let categories = serde_json::to_string(categories)?;
+    let request_url = format!(
+        "{}/api/skipSegments?videoID={}&categories={}",
+        api.trim_end_matches('/'),
+        video_id,
+        urlencoding::encode(&categories)
+    );
+
+    let response = match utils::reqwest_get(&request_url) {
+        Ok(response) => response,
+        Err(Error::ReqwestError(err)) if err.status() == Some(reqwest::StatusCode::NOT_FOUND) => {
+            return Ok(Vec::new())
+        }
+        Err(err) => return Err(err),
+    };
+    let response: serde_json::Value = response.json()?;
+
+    let segments = response
+        .as_array()
+        .ok_or_else(|| Error::OtherError(format!("{:?}", response.to_string())))?
+        .iter()
+        .filter(|segment| {
+            segment
+                .get("actionType")
+                .and_then(|action| action.as_str())
+                .is_none_or(|action| action == "skip")
+        })
+        .filter_map(|segment| {
+            let times = segment.get("segment")?.as_array()?;
+            Some(Segment {
+                start: times.first()?.as_f64()?,
+                end: times.get(1)?.as_f64()?,
+                category: segment.get("category")?.as_str()?.to_string(),
+            })
+        })
+        .collect();
+
+    Ok(segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+
+    fn spawn_stub_server(
+        status: &'static str,
+        body: &'static str,
+    ) -> (String, std::thread::JoinHandle<String>) {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+
+        let handle = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+
+            let mut request = Vec::new();
+            let mut buf = [0; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                let read = stream.read(&mut buf).unwrap();
+                if read == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..read]);
+            }
+
+            let response = format!(
+                "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            stream.write_all(response.as_bytes()).unwrap();
+
+            String::from_utf8(request).unwrap()
+        });
+
+        (address, handle)
+    }
+
+    #[test]
+    fn fetch_segments_from_stub_server() {
+        let body = r#"[
+            {"segment": [0.0, 12.5], "category": "intro", "actionType": "skip"},
+            {"segment": [200.0, 230.0], "category": "music_offtopic", "actionType": "skip"},
+            {"segment": [100.0, 110.0], "category": "sponsor", "actionType": "mute"}
+        ]"#;
+        let (address, handle) = spawn_stub_server("200 OK", body);
+
+        let categories = vec!["intro".to_string(), "music_offtopic".to_string()];
+        let segments = fetch_segments(&address, "dQw4w9WgXcQ", &categories).unwrap();
+        let request = handle.join().unwrap();
+
+        assert!(request.starts_with("GET /api/skipSegments?videoID=dQw4w9WgXcQ&categories="));
+        assert!(request.contains(urlencoding::encode(r#"["intro","music_offtopic"]"#).as_ref()));
+        assert_eq!(
+            segments,
+            vec![
+                Segment {
+                    start: 0.0,
+                    end: 12.5,
+                    category: "intro".to_string(),
+                },
+                Segment {
+                    start: 200.0,
+                    end: 230.0,
+                    category: "music_offtopic".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn fetch_segments_not_found_is_empty() {
+        let (address, handle) = spawn_stub_server("404 Not Found", "Not Found");
+
+        let segments = fetch_segments(&address, "dQw4w9WgXcQ", &["sponsor".to_string()]).unwrap();
+        handle.join().unwrap();
+
+        assert!(segments.is_empty());
+    }
+
+    #[test]
+    fn segment_contains() {
+        let segment = Segment {
+            start: 10.0,
+            end: 20.0,
+            category: "sponsor".to_string(),
+        };
+
+        assert!(!segment.contains(9.9));
+        assert!(segment.contains(10.0));
+        assert!(segment.contains(19.0));
+        assert!(!segment.contains(19.6));
+    }
+}
@@ -0,0 +1,300 @@
+use super::music_source::{Remote, RemoteSourceProcessor, Source};
+use super::{MusicPlayerLogic, MusicPlayerLogicSignals};
+use crate::music_player::Error;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+const PREFETCH_MAX_ATTEMPTS: usize = 2;
+
+#[derive(Debug)]
+pub enum PrefetchResult {
+    Resolved(Box<Remote>),
+    Blocked,
+    Failed,
+}
+
+enum PrefetchJob {
+    Stream {
+        source: Source,
+        remote_src_proc: Arc<RemoteSourceProcessor>,
+    },
+    Related {
+        seed_video_id: String,
+        played_video_ids: Vec<String>,
+        generation: usize,
+        remote_src_proc: Box<RemoteSourceProcessor>,
+    },
+}
+
+pub struct Prefetcher {
+    depth: usize,
+    workers: usize,
+    job_send: Option<crossbeam::channel::Sender<PrefetchJob>>,
+    requested: HashSet<String>,
+    failures: HashMap<String, usize>,
+    blocked: HashSet<String>,
+}
+
+impl Prefetcher {
+    pub fn new(depth: usize, workers: usize) -> Self {
+        Self {
+            depth,
+            workers,
+            job_send: None,
+            requested: HashSet::new(),
+            failures: HashMap::new(),
+            blocked: HashSet::new(),
+        }
+    }
+
+    pub fn start(
+        &mut self,
+        mp_logic_signal_send: crossbeam::channel::Sender<MusicPlayerLogicSignals>,
+    ) {
+        if self.depth == 0 || self.workers == 0 {
+            return;
+        }
+
+        let (s, r) = crossbeam::channel::unbounded();
+        for _ in 0..self.workers {
+            let job_recv = r.clone();
+            let mp_logic_signal_send = mp_logic_signal_send.clone();
+            std::thread::spawn(move || Self::work(job_recv, mp_logic_signal_send));
+        }
+
+        log::info!("Prefetcher::start -> {:?}::{:?}", self.depth, self.workers);
+        self.job_send = Some(s);
+    }
+
+    fn work(
+        job_recv: crossbeam::channel::Receiver<PrefetchJob>,
+        mp_logic_signal_send: crossbeam::channel::Sender<MusicPlayerLogicSignals>,
+    ) {
+        while let Ok(job) = job_recv.recv() {
+            let signal = match job {
+                PrefetchJob::Stream {
+                    source,
+                    remote_src_proc,
+                } => match Self::resolve_stream(source, &remote_src_proc) {
+                    Some((video_id, result)) => {
+                        MusicPlayerLogicSignals::Prefetched(video_id, result)
+                    }
+                    None => continue,
+                },
+                PrefetchJob::Related {
+                    seed_video_id,
+                    played_video_ids,
+                    generation,
+                    mut remote_src_proc,
+                } => {
+                    let result =
+                        Self::find_related(&seed_video_id, played_video_ids, &mut remote_src_proc);
+                    MusicPlayerLogicSignals::PrefetchedRelated(seed_video_id, generation, result)
+                }
+            };
+
+            if mp_logic_signal_send.send(signal).is_err() {
+                break;
+            }
+        }
+    }
+
+    fn resolve_stream(
+        mut source: Source,
+        remote_src_proc: &RemoteSourceProcessor,
+    ) -> Option<(String, PrefetchResult)> {
+        let video_id = match &source {
+            Source::Remote(remote_src) => remote_src.video_id.clone(),
+            _ => return None,
+        };
+        let mut remote_src_proc = remote_src_proc.clone();
+        let result = MusicPlayerLogic::prepare_source_impl(&mut source, &mut remote_src_proc);
+
+        let result = match (result, source) {
+            (Ok(()), Source::Remote(remote_src)) => PrefetchResult::Resolved(Box::new(remote_src)),
+            (Err(Error::VideoBlockedInAllRegions), _)
+            | (Err(Error::VideoBlockedOnCopyRightGrounds), _) => PrefetchResult::Blocked,
+            (result, _) => {
+                log::info!("Prefetcher::resolve_stream -> {:?}::{:?}", video_id, result);
+                PrefetchResult::Failed
+            }
+        };
+
+        Some((video_id, result))
+    }
+
+    fn find_related(
+        seed_video_id: &str,
+        played_video_ids: Vec<String>,
+        remote_src_proc: &mut RemoteSourceProcessor,
+    ) -> Result<Source, Error> {
+        loop {
+            match remote_src_proc.get_related_video_source(seed_video_id, &played_video_ids) {
+                Err(err @ Error::VideoBlockedInAllRegions)
+                | Err(err @ Error::VideoBlockedOnCopyRightGrounds)
+                | Err(err @ Error::AllInvidiousApiDomainsDown(_)) => return Err(err),
+                Err(err) => {
+                    if remote_src_proc.next_piped_api_domains_index().is_err() {
+                        log::info!("Prefetcher::find_related -> {:?}", err);
+                        return Err(err);
+                    }
+                }
+                result => return result,
+            }
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.job_send.is_some()
+    }
+
+    pub fn get_depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn is_blocked(&self, video_id: &str) -> bool {
+        self.blocked.contains(video_id)
+    }
+
+    pub fn request(&mut self, remote_src: &Remote, remote_src_proc: &Arc<RemoteSourceProcessor>) {
+        let job_send = match &self.job_send {
+            Some(job_send) => job_send,
+            None => return,
+        };
+        let failures = self.failures.get(&remote_src.video_id).copied();
+        if failures.unwrap_or(0) >= PREFETCH_MAX_ATTEMPTS
+            || !self.requested.insert(remote_src.video_id.clone())
+        {
+            return;
+        }
+
+        log::info!("Prefetcher::request -> {:?}", remote_src.video_id);
+
+        let mut source = Source::Remote(remote_src.clone());
+        if let Source::Remote(remote_src) = &mut source {
+            remote_src.audio_stream_url.clear();
+        }
+        job_send
+            .send(PrefetchJob::Stream {
+                source,
+                remote_src_proc: Arc::clone(remote_src_proc),
+            })
+            .unwrap();
+    }
+
+    pub fn request_related(
+        &self,
+        seed_video_id: &str,
+        played_video_ids: &[String],
+        generation: usize,
+        remote_src_proc: RemoteSourceProcessor,
+    ) {
+        let job_send = match &self.job_send {
+            Some(job_send) => job_send,
+            None => return,
+        };
+
+        log::info!("Prefetcher::request_related -> {:?}", seed_video_id);
+
+        job_send
+            .send(PrefetchJob::Related {
+                seed_video_id: seed_video_id.to_string(),
+                played_video_ids: played_video_ids.to_vec(),
+                generation,
+                remote_src_proc: Box::new(remote_src_proc),
+            })
+            .unwrap();
+    }
+
+    pub fn finish(&mut self, video_id: &str, result: &PrefetchResult) {
+        match result {
+            PrefetchResult::Resolved(_) => {
+                self.requested.remove(video_id);
+                self.failures.remove(video_id);
+            }
+            PrefetchResult::Blocked => {
+                self.blocked.insert(video_id.to_string());
+            }
+            PrefetchResult::Failed => {
+                self.requested.remove(video_id);
+                *self.failures.entry(video_id.to_string()).or_insert(0) += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prefetcher() -> (Prefetcher, crossbeam::channel::Receiver<PrefetchJob>) {
+        let (s, r) = crossbeam::channel::unbounded();
+        let mut prefetcher = Prefetcher::new(2, 1);
+        prefetcher.job_send = Some(s);
+
+        (prefetcher, r)
+    }
+
+    fn remote_src_proc() -> Arc<RemoteSourceProcessor> {
+        Arc::new(RemoteSourceProcessor::new(vec![], 0, vec![], 0, 600).unwrap())
+    }
+
+    #[test]
+    fn failed_prefetch_is_retried_up_to_the_limit() {
+        let (mut prefetcher, job_recv) = prefetcher();
+        let remote_src = Remote::new("https://www.youtube.com/watch?v=dQw4w9WgXcQ").unwrap();
+        let remote_src_proc = remote_src_proc();
+
+        for _ in 0..PREFETCH_MAX_ATTEMPTS {
+            prefetcher.request(&remote_src, &remote_src_proc);
+            prefetcher.request(&remote_src, &remote_src_proc);
+            assert_eq!(job_recv.try_iter().count(), 1);
+
+            prefetcher.finish(&remote_src.video_id, &PrefetchResult::Failed);
+        }
+
+        prefetcher.request(&remote_src, &remote_src_proc);
+        assert_eq!(job_recv.try_iter().count(), 0);
+    }
+
+    #[test]
+    fn blocked_prefetch_is_not_retried() {
+        let (mut prefetcher, job_recv) = prefetcher();
+        let remote_src = Remote::new("https://www.youtube.com/watch?v=dQw4w9WgXcQ").unwrap();
+        let remote_src_proc = remote_src_proc();
+
+        prefetcher.request(&remote_src, &remote_src_proc);
+        prefetcher.finish(&remote_src.video_id, &PrefetchResult::Blocked);
+        prefetcher.request(&remote_src, &remote_src_proc);
+
+        assert_eq!(job_recv.try_iter().count(), 1);
+        assert!(prefetcher.is_blocked(&remote_src.video_id));
+    }
+
+    #[test]
+    fn related_request_is_queued_for_the_workers() {
+        let (prefetcher, job_recv) = prefetcher();
+        let remote_src_proc = RemoteSourceProcessor::clone(&remote_src_proc());
+
+        prefetcher.request_related(
+            "dQw4w9WgXcQ",
+            &["9bZkp7q19f0".to_string()],
+            3,
+            remote_src_proc,
+        );
+
+        match job_recv.try_recv().unwrap() {
+            PrefetchJob::Related {
+                seed_video_id,
+                played_video_ids,
+                generation,
+                ..
+            } => {
+                assert_eq!(seed_video_id, "dQw4w9WgXcQ");
+                assert_eq!(played_video_ids, vec!["9bZkp7q19f0"]);
+                assert_eq!(generation, 3);
+            }
+            PrefetchJob::Stream { .. } => panic!(),
+        }
+    }
+}
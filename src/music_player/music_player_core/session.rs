@@ -0,0 +1,174 @@
+use crate::music_player::music_player_core::music_source::Source;
+use crate::music_player::Error;
+use std::collections::VecDeque;
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct SessionSource {
+    pub source_type: String,
+    pub url: String,
+    pub title: String,
+    pub length: u64,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct SessionState {
+    pub queue: Vec<SessionSource>,
+    pub to_play_index: usize,
+    pub related_queue: VecDeque<String>,
+    pub played_video_ids: Vec<String>,
+    pub position_s: f64,
+    pub volume: i64,
+}
+
+impl SessionState {
+    pub fn new(
+        to_play: &[Source],
+        to_play_index: usize,
+        related_queue: &VecDeque<String>,
+        played_video_ids: &[String],
+        position_s: f64,
+        volume: i64,
+    ) -> Self {
+        let queue = to_play
+            .iter()
+            .map(|source| {
+                let source_type = match source {
+                    Source::Remote(_) => "remote",
+                    Source::Local(_) => "local",
+                    Source::Stream(_) => "stream",
+                };
+
+                SessionSource {
+                    source_type: source_type.to_string(),
+                    url: source.to_url(),
+                    title: source.get_title().to_string(),
+                    length: source.get_length(),
+                }
+            })
+            .collect();
+
+        Self {
+            queue,
+            to_play_index,
+            related_queue: related_queue.clone(),
+            played_video_ids: played_video_ids.to_vec(),
+            position_s,
+            volume,
+        }
+    }
+
+    pub fn load(path: &str) -> Result<Self, Error> {
+        let session = std::fs::read_to_string(path).map_err(|err| {
+            Error::InvalidSessionFile(format!("Unable to read session file {path}: {err}"))
+        })?;
+        let session: Self = serde_json::from_str(&session)?;
+
+        log::info!(
+            "SessionState::load -> {:?}::{:?}",
+            session.queue.len(),
+            session.to_play_index
+        );
+
+        Ok(session)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), Error> {
+        let session = serde_json::to_string_pretty(self)?;
+
+        let tmp_path = format!("{path}.tmp");
+        std::fs::write(&tmp_path, session)?;
+        std::fs::rename(&tmp_path, path)?;
+
+        Ok(())
+    }
+
+    pub fn current_index(&self) -> usize {
+        self.to_play_index.saturating_sub(1)
+    }
+
+    pub fn to_source_vec(&self) -> Vec<Source> {
+        self.queue
+            .iter()
+            .skip(self.current_index())
+            .filter_map(|session_src| {
+                let source = match session_src.source_type.as_str() {
+                    "remote" => Source::new_remote(&session_src.url),
+                    "local" => Source::new_local(&session_src.url),
+                    _ => Source::new_stream(&session_src.url),
+                };
+
+                match source {
+                    Ok(mut source) => {
+                        source.set_title_length(
+                            Some(session_src.title.to_string()),
+                            Some(session_src.length),
+                        );
+                        Some(source)
+                    }
+                    Err(err) => {
+                        log::info!("SessionState::to_source_vec -> Skipping: {:?}", err);
+                        None
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn session_survives_save_and_load() {
+        let dir = crate::utils::temp_dir("session");
+        let local_path = dir.join("song.mp3").to_string_lossy().to_string();
+        std::fs::write(&local_path, b"").unwrap();
+
+        let mut to_play = vec![
+            Source::new_remote("https://www.youtube.com/watch?v=dQw4w9WgXcQ").unwrap(),
+            Source::new_local(&local_path).unwrap(),
+            Source::new_stream("https://radio.example/stream").unwrap(),
+        ];
+        for (i, source) in to_play.iter_mut().enumerate() {
+            source.set_title_length(Some(format!("Title {i}")), Some(100 + i as u64));
+        }
+        let related_queue = VecDeque::from(["dQw4w9WgXcQ".to_string(), "9bZkp7q19f0".to_string()]);
+        let played_video_ids = vec!["dQw4w9WgXcQ".to_string()];
+
+        let path = dir.join("session.json").to_string_lossy().to_string();
+        SessionState::new(&to_play, 2, &related_queue, &played_video_ids, 42.5, 70)
+            .save(&path)
+            .unwrap();
+        let session = SessionState::load(&path).unwrap();
+
+        assert_eq!(session.queue.len(), 3);
+        assert_eq!(session.to_play_index, 2);
+        assert_eq!(session.current_index(), 1);
+        assert_eq!(session.related_queue, related_queue);
+        assert_eq!(session.played_video_ids, played_video_ids);
+        assert_eq!(session.position_s, 42.5);
+        assert_eq!(session.volume, 70);
+
+        let sources = session.to_source_vec();
+        assert_eq!(sources.len(), 2);
+        assert_eq!(sources[0].to_url(), local_path);
+        assert_eq!(sources[0].get_title(), "Title 1");
+        assert_eq!(sources[0].get_length(), 101);
+        assert!(matches!(&sources[1], Source::Stream(_)));
+        assert_eq!(sources[1].to_url(), "https://radio.example/stream");
+        assert_eq!(sources[1].get_title(), "Title 2");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn missing_session_file_is_reported() {
+        let path = crate::utils::temp_path("session_missing.json");
+
+        assert!(matches!(
+            SessionState::load(&path.to_string_lossy()),
+            Err(Error::InvalidSessionFile(_))
+        ));
+    }
+}
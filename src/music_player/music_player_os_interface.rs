@@ -1,4 +1,4 @@
-use crate::music_player::libmpv_handlers::LibMpvSignals;
+use crate::music_player::libmpv_handlers::{LibMpvSignals, SeekTarget};
 use crate::music_player::music_player_core::MusicPlayerLogicSignals;
 
 #[derive(Debug)]
@@ -7,6 +7,7 @@ pub enum OSInterfaceSignals {
     Resume,
     PlayNext,
     PlayPrev,
+    Seek(SeekTarget),
     UpdateMetadataTitle(String),
     End,
 }
@@ -72,6 +73,31 @@ impl MediaPlayerOSInterface {
                 souvlaki::MediaControlEvent::Previous => {
                     signal_sender.send(OSInterfaceSignals::PlayPrev).unwrap();
                 }
+                souvlaki::MediaControlEvent::Seek(direction) => {
+                    let secs = match direction {
+                        souvlaki::SeekDirection::Forward => 10.0,
+                        souvlaki::SeekDirection::Backward => -10.0,
+                    };
+                    signal_sender
+                        .send(OSInterfaceSignals::Seek(SeekTarget::Relative(secs)))
+                        .unwrap();
+                }
+                souvlaki::MediaControlEvent::SeekBy(direction, duration) => {
+                    let secs = match direction {
+                        souvlaki::SeekDirection::Forward => duration.as_secs_f64(),
+                        souvlaki::SeekDirection::Backward => -duration.as_secs_f64(),
+                    };
+                    signal_sender
+                        .send(OSInterfaceSignals::Seek(SeekTarget::Relative(secs)))
+                        .unwrap();
+                }
+                souvlaki::MediaControlEvent::SetPosition(souvlaki::MediaPosition(position)) => {
+                    signal_sender
+                        .send(OSInterfaceSignals::Seek(SeekTarget::Absolute(
+                            position.as_secs_f64(),
+                        )))
+                        .unwrap();
+                }
                 _ => (),
             })
             .unwrap();
@@ -124,6 +150,11 @@ impl MediaPlayerOSInterface {
                                 .unwrap();
                             libmpv_signal_send.send(LibMpvSignals::PlayPrev).unwrap();
                         }
+                        OSInterfaceSignals::Seek(target) => {
+                            libmpv_signal_send
+                                .send(LibMpvSignals::Seek(target))
+                                .unwrap();
+                        }
                         OSInterfaceSignals::UpdateMetadataTitle(title) => {
                             self.media_controller
                                 .set_metadata(souvlaki::MediaMetadata {
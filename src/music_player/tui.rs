@@ -1,6 +1,8 @@
 pub mod commands;
 pub mod user_input_handler;
 
+use crate::music_player::music_player_core::music_source::lyrics::Lyrics;
+use crate::music_player::music_player_core::RepeatMode;
 use crossterm::{
     event::{DisableMouseCapture, EnableMouseCapture},
     execute,
@@ -8,6 +10,8 @@ use crossterm::{
 };
 use ratatui::{
     backend::CrosstermBackend,
+    style::{Modifier, Style},
+    text::{Line, Text},
     widgets::{Block, Borders},
     Terminal,
 };
@@ -25,9 +29,19 @@ pub enum TuiSignals {
     PlaybackResume,
     UpdateTitle(String),
     UpdateDuration(u64),
+    UpdateAudioFormat(String),
+    UpdateLyrics(Lyrics),
+    UpdatePlaybackTime(f64),
+    UpdatePlaybackDuration(f64),
+    UpdateBuffering(bool),
     UpdateState(TuiState),
     UpdateHelpStr(String),
     UpdateVolume(i64),
+    UpdateRepeat(RepeatMode),
+    UpdateStatus(String),
+    UpdatePastHistory(Vec<String>),
+    UpdateQueue(Vec<String>, usize, usize),
+    UpdateSearchResults(Vec<String>, usize),
     ModifyScroll(i16),
     Quit,
 }
@@ -36,6 +50,9 @@ pub enum TuiSignals {
 pub enum TuiState {
     Player,
     History,
+    Queue,
+    Search,
+    Lyrics,
     Help,
 }
 
@@ -44,6 +61,9 @@ impl TuiState {
         match self {
             TuiState::Player => "player",
             TuiState::History => "history",
+            TuiState::Queue => "queue",
+            TuiState::Search => "search",
+            TuiState::Lyrics => "lyrics",
             TuiState::Help => "help",
         }
     }
@@ -94,6 +114,10 @@ impl MusicPlayerTUI {
     }
 
     pub fn draw(&mut self, text: &str, scroll: u16, command: Option<&str>) {
+        self.draw_text(Text::from(text), scroll, command);
+    }
+
+    pub fn draw_text(&mut self, text: Text, scroll: u16, command: Option<&str>) {
         self.terminal
             .draw(|f| {
                 let size = f.size();
@@ -118,14 +142,24 @@ impl MusicPlayerTUI {
     pub fn handle_signals(&mut self) {
         let mut title = "".to_string();
         let mut history: Vec<String> = Vec::new();
+        let mut past_history: Vec<String> = Vec::new();
+        let mut queue: Vec<String> = Vec::new();
+        let mut queue_current = 0;
+        let mut queue_cursor = 0;
+        let mut search_results: Vec<String> = Vec::new();
+        let mut search_cursor = 0;
         let mut duration = 0;
-        let mut playback_start = std::time::SystemTime::now();
-        let mut playback_start_offset = 0.0;
+        let mut audio_format = "".to_string();
+        let mut lyrics = Lyrics::default();
+        let mut playback_time = 0.0;
         let mut playback_paused = true;
+        let mut buffering = false;
         let mut audio_ready = false;
         let mut scroll: u16 = 0;
         let mut command_text = None;
         let mut help_text = "".to_string();
+        let mut status = "".to_string();
+        let mut repeat = RepeatMode::Related;
 
         loop {
             std::thread::sleep(std::time::Duration::from_millis(16));
@@ -148,23 +182,23 @@ impl MusicPlayerTUI {
                         }
                         TuiSignals::Start => audio_ready = false,
                         TuiSignals::AudioReady => {
-                            playback_start = std::time::SystemTime::now();
                             audio_ready = true;
                             playback_paused = false;
                         }
                         TuiSignals::End => {
-                            playback_start_offset = 0.0;
-                            playback_paused = true;
-                        }
-                        TuiSignals::PlaybackPause => {
-                            playback_start_offset +=
-                                playback_start.elapsed().unwrap().as_secs_f64();
+                            playback_time = 0.0;
                             playback_paused = true;
+                            buffering = false;
                         }
-                        TuiSignals::PlaybackResume => {
-                            playback_start = std::time::SystemTime::now();
-                            playback_paused = false;
+                        TuiSignals::PlaybackPause => playback_paused = true,
+                        TuiSignals::PlaybackResume => playback_paused = false,
+                        TuiSignals::UpdatePlaybackTime(time) => playback_time = time,
+                        TuiSignals::UpdatePlaybackDuration(dur) => {
+                            if dur > 0.0 {
+                                duration = dur.ceil() as u64;
+                            }
                         }
+                        TuiSignals::UpdateBuffering(b) => buffering = b,
                         TuiSignals::UpdateTitle(t) => {
                             title = t.clone();
                             let t = t.replace('\n', " ");
@@ -186,14 +220,46 @@ impl MusicPlayerTUI {
                         TuiSignals::UpdateVolume(volume) => {
                             self.volume = volume;
                         }
+                        TuiSignals::UpdateRepeat(r) => {
+                            repeat = r;
+                        }
+                        TuiSignals::UpdateStatus(s) => {
+                            status = s;
+                        }
+                        TuiSignals::UpdatePastHistory(lines) => {
+                            past_history = lines;
+                        }
+                        TuiSignals::UpdateQueue(titles, current, cursor) => {
+                            queue = titles;
+                            queue_current = current;
+                            queue_cursor = cursor;
+                        }
+                        TuiSignals::UpdateSearchResults(results, cursor) => {
+                            search_results = results;
+                            search_cursor = cursor;
+                        }
                         TuiSignals::UpdateDuration(dur) => {
                             duration = dur;
                         }
+                        TuiSignals::UpdateAudioFormat(format) => {
+                            audio_format = format;
+                        }
+                        TuiSignals::UpdateLyrics(l) => {
+                            lyrics = l;
+                            if self.tui_state == TuiState::Lyrics {
+                                scroll = 0;
+                            }
+                        }
                         TuiSignals::Quit => {
                             break;
                         }
                         TuiSignals::ModifyScroll(x) => {
-                            if x > 0 && scroll < (history.len() - 1) as u16 {
+                            let lines = match self.tui_state {
+                                TuiState::Help => help_text.lines().count(),
+                                TuiState::Lyrics => lyrics.plain.len(),
+                                _ => history.len() + past_history.len(),
+                            };
+                            if x > 0 && scroll < lines.saturating_sub(1) as u16 {
                                 scroll += 1;
                             } else if x < 0 && scroll > 0 {
                                 scroll -= 1;
@@ -204,20 +270,21 @@ impl MusicPlayerTUI {
             }
             match self.tui_state {
                 TuiState::Player => {
-                    let playback_time = {
+                    let mut playback_time = {
                         if !audio_ready {
-                            0.0
-                        } else if playback_paused {
-                            playback_start_offset
+                            0
                         } else {
-                            playback_start_offset + playback_start.elapsed().unwrap().as_secs_f64()
+                            playback_time as u64
                         }
                     };
-                    let mut playback_time = playback_time.ceil() as u64;
-                    playback_time = playback_time.min(duration);
+                    if duration > 0 {
+                        playback_time = playback_time.min(duration);
+                    }
                     let symbol = {
                         if !audio_ready {
                             "|"
+                        } else if buffering {
+                            "~"
                         } else if playback_paused {
                             "|"
                         } else {
@@ -226,18 +293,90 @@ impl MusicPlayerTUI {
                     };
                     let mut to_draw = title.clone();
                     to_draw.push_str(&format!(
-                        "\n{} {} / {} vol: {}",
-                        symbol, playback_time, duration, self.volume
+                        "\n{} {} / {} vol: {} repeat: {}",
+                        symbol,
+                        playback_time,
+                        duration,
+                        self.volume,
+                        repeat.to_str()
                     ));
+                    if buffering {
+                        to_draw.push_str(" buffering...");
+                    }
+                    if !audio_format.is_empty() {
+                        to_draw.push_str(&format!("\n{audio_format}"));
+                    }
+                    if !status.is_empty() {
+                        to_draw.push_str(&format!("\n{status}"));
+                    }
                     self.draw(&to_draw, 0, command_text.as_deref());
                 }
                 TuiState::History => {
                     let mut to_draw = "".to_string();
+                    past_history
+                        .iter()
+                        .for_each(|x| to_draw.push_str(&format!("{x}\n")));
+                    if !past_history.is_empty() {
+                        to_draw.push_str("Session: current\n");
+                    }
                     history
                         .iter()
                         .for_each(|x| to_draw.push_str(&format!("{x}\n")));
                     self.draw(&to_draw, scroll, command_text.as_deref());
                 }
+                TuiState::Queue => {
+                    let mut to_draw = "".to_string();
+                    queue.iter().enumerate().for_each(|(i, x)| {
+                        let cursor = if i == queue_cursor { "*" } else { " " };
+                        let current = if i == queue_current { ">" } else { " " };
+                        to_draw.push_str(&format!("{cursor}{current} {i}: {x}\n"));
+                    });
+                    let queue_scroll = queue_cursor.saturating_sub(5) as u16;
+                    self.draw(&to_draw, queue_scroll, command_text.as_deref());
+                }
+                TuiState::Search => {
+                    let mut to_draw = "".to_string();
+                    if !status.is_empty() {
+                        to_draw.push_str(&format!("{status}\n"));
+                    }
+                    search_results.iter().enumerate().for_each(|(i, x)| {
+                        let cursor = if i == search_cursor { "*" } else { " " };
+                        to_draw.push_str(&format!("{cursor} {i}: {x}\n"));
+                    });
+                    let search_scroll = search_cursor.saturating_sub(5) as u16;
+                    self.draw(&to_draw, search_scroll, command_text.as_deref());
+                }
+                TuiState::Lyrics => {
+                    if lyrics.timed.is_empty() {
+                        let mut to_draw = lyrics.plain.join("\n");
+                        if lyrics.is_empty() {
+                            to_draw = status.clone();
+                        }
+                        self.draw(&to_draw, scroll, command_text.as_deref());
+                    } else {
+                        let current = lyrics
+                            .timed
+                            .iter()
+                            .rposition(|(time, _)| *time <= playback_time);
+                        let lines: Vec<Line> = lyrics
+                            .timed
+                            .iter()
+                            .enumerate()
+                            .map(|(i, (_, text))| {
+                                if Some(i) == current {
+                                    Line::styled(
+                                        format!("> {text}"),
+                                        Style::default().add_modifier(Modifier::BOLD),
+                                    )
+                                } else {
+                                    Line::from(format!("  {text}"))
+                                }
+                            })
+                            .collect();
+                        let lyrics_scroll = current.unwrap_or_default().saturating_sub(5) as u16;
+                        self.draw_text(Text::from(lines), lyrics_scroll, command_text.as_deref());
+                    }
+                }
                 TuiState::Help => {
                     self.draw(&help_text, scroll, command_text.as_deref());
                 }
@@ -17,12 +17,20 @@ impl TuiCommands {
     pub fn new() -> Self {
         let global_commands = Self::init_global_commands();
         let states_commands = HashMap::from([
+            (TuiState::Player, Self::init_player_state_commands()),
             (TuiState::History, Self::init_history_state_commands()),
+            (TuiState::Queue, Self::init_queue_state_commands()),
+            (TuiState::Search, Self::init_search_state_commands()),
+            (TuiState::Lyrics, Self::init_help_state_commands()),
             (TuiState::Help, Self::init_help_state_commands()),
         ]);
         let global_commands_keys = Self::init_global_commands_keys();
         let states_commands_keys = HashMap::from([
+            (TuiState::Player, Self::init_player_state_commands_keys()),
             (TuiState::History, Self::init_history_state_commands_keys()),
+            (TuiState::Queue, Self::init_queue_state_commands_keys()),
+            (TuiState::Search, Self::init_search_state_commands_keys()),
+            (TuiState::Lyrics, Self::init_help_state_commands_keys()),
             (TuiState::Help, Self::init_help_state_commands_keys()),
         ]);
         Self {
@@ -205,12 +213,102 @@ impl TuiCommands {
                 args: vec![],
                 user_facing: true,
             },
+            CommandDefinition {
+                name: "seek".to_string(),
+                action_type: CommandType::Seek,
+                args: vec![Arg::SEEKTARGET(None)],
+                user_facing: true,
+            },
+            CommandDefinition {
+                name: "add".to_string(),
+                action_type: CommandType::AddToQueue,
+                args: vec![Arg::STRING(None)],
+                user_facing: true,
+            },
+            CommandDefinition {
+                name: "add-next".to_string(),
+                action_type: CommandType::AddNextToQueue,
+                args: vec![Arg::STRING(None)],
+                user_facing: true,
+            },
+            CommandDefinition {
+                name: "add-playlist".to_string(),
+                action_type: CommandType::AddPlaylistToQueue,
+                args: vec![Arg::STRING(None)],
+                user_facing: true,
+            },
+            CommandDefinition {
+                name: "repeat".to_string(),
+                action_type: CommandType::Repeat,
+                args: vec![Arg::REPEATMODE(None)],
+                user_facing: true,
+            },
+            CommandDefinition {
+                name: "repeat-cycle".to_string(),
+                action_type: CommandType::CycleRepeat,
+                args: vec![],
+                user_facing: true,
+            },
+            CommandDefinition {
+                name: "shuffle".to_string(),
+                action_type: CommandType::ToggleShuffle,
+                args: vec![],
+                user_facing: true,
+            },
+            CommandDefinition {
+                name: "like".to_string(),
+                action_type: CommandType::Like,
+                args: vec![],
+                user_facing: true,
+            },
+            CommandDefinition {
+                name: "dislike".to_string(),
+                action_type: CommandType::Dislike,
+                args: vec![],
+                user_facing: true,
+            },
+            CommandDefinition {
+                name: "download".to_string(),
+                action_type: CommandType::Download,
+                args: vec![],
+                user_facing: true,
+            },
+            CommandDefinition {
+                name: "search".to_string(),
+                action_type: CommandType::Search,
+                args: vec![Arg::STRING(None)],
+                user_facing: true,
+            },
             CommandDefinition {
                 name: "copy".to_string(),
                 action_type: CommandType::CopyVideoURL,
                 args: vec![],
                 user_facing: true,
             },
+            CommandDefinition {
+                name: "export-m3u8".to_string(),
+                action_type: CommandType::ExportM3U8,
+                args: vec![],
+                user_facing: true,
+            },
+            CommandDefinition {
+                name: "export-json".to_string(),
+                action_type: CommandType::ExportJSON,
+                args: vec![],
+                user_facing: true,
+            },
+            CommandDefinition {
+                name: "export-m3u8-to".to_string(),
+                action_type: CommandType::ExportM3U8To,
+                args: vec![Arg::STRING(None)],
+                user_facing: true,
+            },
+            CommandDefinition {
+                name: "export-json-to".to_string(),
+                action_type: CommandType::ExportJSONTo,
+                args: vec![Arg::STRING(None)],
+                user_facing: true,
+            },
         ]);
 
         global_commands
@@ -221,6 +319,9 @@ impl TuiCommands {
             (KeyCode::Char(':'), "enter_command_mode".to_string()),
             (KeyCode::Char('1'), "view player".to_string()),
             (KeyCode::Char('2'), "view history".to_string()),
+            (KeyCode::Char('3'), "view queue".to_string()),
+            (KeyCode::Char('4'), "view search".to_string()),
+            (KeyCode::Char('5'), "view lyrics".to_string()),
             (KeyCode::Char('0'), "view help".to_string()),
             (KeyCode::Char('q'), "quit".to_string()),
             (KeyCode::Char('{'), "vol -1".to_string()),
@@ -230,6 +331,12 @@ impl TuiCommands {
             (KeyCode::Char(' '), "player-pause-resume".to_string()),
             (KeyCode::Char('b'), "player-next".to_string()),
             (KeyCode::Char('z'), "player-prev".to_string()),
+            (KeyCode::Char('r'), "repeat-cycle".to_string()),
+            (KeyCode::Char('s'), "shuffle".to_string()),
+            (KeyCode::Char('+'), "like".to_string()),
+            (KeyCode::Char('-'), "dislike".to_string()),
+            (KeyCode::Left, "seek -10".to_string()),
+            (KeyCode::Right, "seek +10".to_string()),
         ])
     }
     fn generate_keys_help_str(key_map: &HashMap<KeyCode, String>) -> String {
@@ -252,14 +359,41 @@ impl TuiCommands {
         help_str.join("\n")
     }
 
+    fn init_player_state_commands() -> CommandsRegistry {
+        let mut player_state_commands = CommandsRegistry::new();
+        player_state_commands.add_commands([CommandDefinition {
+            name: "seek".to_string(),
+            action_type: CommandType::Seek,
+            args: vec![Arg::SEEKTARGET(None)],
+            user_facing: false,
+        }]);
+
+        player_state_commands
+    }
+
+    fn init_player_state_commands_keys() -> HashMap<KeyCode, String> {
+        HashMap::from([
+            (KeyCode::Down, "seek -60".to_string()),
+            (KeyCode::Up, "seek +60".to_string()),
+        ])
+    }
+
     fn init_history_state_commands() -> CommandsRegistry {
         let mut history_state_commands = CommandsRegistry::new();
-        history_state_commands.add_commands([CommandDefinition {
-            name: "scroll".to_string(),
-            action_type: CommandType::Scroll,
-            args: vec![Arg::I16(None)],
-            user_facing: true,
-        }]);
+        history_state_commands.add_commands([
+            CommandDefinition {
+                name: "scroll".to_string(),
+                action_type: CommandType::Scroll,
+                args: vec![Arg::I16(None)],
+                user_facing: true,
+            },
+            CommandDefinition {
+                name: "load".to_string(),
+                action_type: CommandType::LoadHistory,
+                args: vec![Arg::USIZE(None)],
+                user_facing: true,
+            },
+        ]);
 
         history_state_commands
     }
@@ -268,6 +402,111 @@ impl TuiCommands {
         HashMap::from([
             (KeyCode::Char('j'), "scroll +1".to_string()),
             (KeyCode::Char('k'), "scroll -1".to_string()),
+            (KeyCode::Down, "scroll +1".to_string()),
+            (KeyCode::Up, "scroll -1".to_string()),
+            (KeyCode::Char('l'), "load 1".to_string()),
+        ])
+    }
+
+    fn init_queue_state_commands() -> CommandsRegistry {
+        let mut queue_state_commands = CommandsRegistry::new();
+        queue_state_commands.add_commands([
+            CommandDefinition {
+                name: "select".to_string(),
+                action_type: CommandType::QueueSelect,
+                args: vec![Arg::I16(None)],
+                user_facing: true,
+            },
+            CommandDefinition {
+                name: "jump".to_string(),
+                action_type: CommandType::QueueJump,
+                args: vec![],
+                user_facing: true,
+            },
+            CommandDefinition {
+                name: "remove".to_string(),
+                action_type: CommandType::QueueRemove,
+                args: vec![],
+                user_facing: true,
+            },
+            CommandDefinition {
+                name: "move-up".to_string(),
+                action_type: CommandType::QueueMoveUp,
+                args: vec![],
+                user_facing: true,
+            },
+            CommandDefinition {
+                name: "move-down".to_string(),
+                action_type: CommandType::QueueMoveDown,
+                args: vec![],
+                user_facing: true,
+            },
+            CommandDefinition {
+                name: "clear-upcoming".to_string(),
+                action_type: CommandType::QueueClearUpcoming,
+                args: vec![],
+                user_facing: true,
+            },
+        ]);
+
+        queue_state_commands
+    }
+
+    fn init_queue_state_commands_keys() -> HashMap<KeyCode, String> {
+        HashMap::from([
+            (KeyCode::Char('j'), "select +1".to_string()),
+            (KeyCode::Char('k'), "select -1".to_string()),
+            (KeyCode::Down, "select +1".to_string()),
+            (KeyCode::Up, "select -1".to_string()),
+            (KeyCode::Enter, "jump".to_string()),
+            (KeyCode::Char('d'), "remove".to_string()),
+            (KeyCode::Char('K'), "move-up".to_string()),
+            (KeyCode::Char('J'), "move-down".to_string()),
+            (KeyCode::Char('c'), "clear-upcoming".to_string()),
+        ])
+    }
+
+    fn init_search_state_commands() -> CommandsRegistry {
+        let mut search_state_commands = CommandsRegistry::new();
+        search_state_commands.add_commands([
+            CommandDefinition {
+                name: "select".to_string(),
+                action_type: CommandType::SearchSelect,
+                args: vec![Arg::I16(None)],
+                user_facing: true,
+            },
+            CommandDefinition {
+                name: "play".to_string(),
+                action_type: CommandType::SearchPlay,
+                args: vec![],
+                user_facing: true,
+            },
+            CommandDefinition {
+                name: "enqueue".to_string(),
+                action_type: CommandType::SearchEnqueue,
+                args: vec![],
+                user_facing: true,
+            },
+            CommandDefinition {
+                name: "enqueue-next".to_string(),
+                action_type: CommandType::SearchEnqueueNext,
+                args: vec![],
+                user_facing: true,
+            },
+        ]);
+
+        search_state_commands
+    }
+
+    fn init_search_state_commands_keys() -> HashMap<KeyCode, String> {
+        HashMap::from([
+            (KeyCode::Char('j'), "select +1".to_string()),
+            (KeyCode::Char('k'), "select -1".to_string()),
+            (KeyCode::Down, "select +1".to_string()),
+            (KeyCode::Up, "select -1".to_string()),
+            (KeyCode::Enter, "play".to_string()),
+            (KeyCode::Char('a'), "enqueue".to_string()),
+            (KeyCode::Char('n'), "enqueue-next".to_string()),
         ])
     }
 
@@ -287,6 +526,8 @@ impl TuiCommands {
         HashMap::from([
             (KeyCode::Char('j'), "scroll +1".to_string()),
             (KeyCode::Char('k'), "scroll -1".to_string()),
+            (KeyCode::Down, "scroll +1".to_string()),
+            (KeyCode::Up, "scroll -1".to_string()),
         ])
     }
 }
@@ -1,3 +1,5 @@
+use crate::music_player::libmpv_handlers::SeekTarget;
+use crate::music_player::music_player_core::RepeatMode;
 use crate::music_player::tui::TuiState;
 use action_to_type_macro::ActionToType;
 use std::collections::hash_map::HashMap;
@@ -16,6 +18,9 @@ pub enum Arg {
     I64(Option<i64>),
     I16(Option<i16>),
     U64(Option<u64>),
+    SEEKTARGET(Option<SeekTarget>),
+    STRING(Option<String>),
+    REPEATMODE(Option<RepeatMode>),
 }
 
 impl Arg {
@@ -61,6 +66,27 @@ impl Arg {
         }
     }
 
+    pub fn extract_seektarget(self) -> Option<SeekTarget> {
+        match self {
+            Arg::SEEKTARGET(seek_target) => seek_target,
+            _ => None,
+        }
+    }
+
+    pub fn extract_string(self) -> Option<String> {
+        match self {
+            Arg::STRING(string) => string,
+            _ => None,
+        }
+    }
+
+    pub fn extract_repeatmode(self) -> Option<RepeatMode> {
+        match self {
+            Arg::REPEATMODE(repeat_mode) => repeat_mode,
+            _ => None,
+        }
+    }
+
     pub fn to_type_str(&self) -> &'static str {
         match self {
             Arg::TUISTATE(_) => "TuiState",
@@ -69,6 +95,9 @@ impl Arg {
             Arg::I16(_) => "i16",
             Arg::I64(_) => "i64",
             Arg::U64(_) => "u64",
+            Arg::SEEKTARGET(_) => "SeekTarget",
+            Arg::STRING(_) => "String",
+            Arg::REPEATMODE(_) => "off|one|all|related",
         }
     }
 }
@@ -113,6 +142,15 @@ impl CommandsRegistry {
         let action = &command.action;
         let action_args = &command.args;
 
+        let joined_arg;
+        let mut args = args;
+        if let Some(Arg::STRING(_)) = action_args.last() {
+            if args.len() > action_args.len() {
+                joined_arg = args.split_off(action_args.len() - 1).join(" ");
+                args.push(&joined_arg);
+            }
+        }
+
         if action_args.len() != args.len() {
             return None;
         }
@@ -123,6 +161,9 @@ impl CommandsRegistry {
                 Arg::TUISTATE(_) => Arg::TUISTATE(match *arg {
                     "player" => Some(TuiState::Player),
                     "history" => Some(TuiState::History),
+                    "queue" => Some(TuiState::Queue),
+                    "search" => Some(TuiState::Search),
+                    "lyrics" => Some(TuiState::Lyrics),
                     "help" => Some(TuiState::Help),
                     _ => None,
                 }),
@@ -131,6 +172,9 @@ impl CommandsRegistry {
                 Arg::USIZE(_) => Arg::USIZE(Some(arg.parse().ok()?)),
                 Arg::U64(_) => Arg::U64(Some(arg.parse().ok()?)),
                 Arg::BOOL(_) => Arg::BOOL(Some(arg.parse().ok()?)),
+                Arg::SEEKTARGET(_) => Arg::SEEKTARGET(Some(SeekTarget::parse(arg)?)),
+                Arg::STRING(_) => Arg::STRING(Some(arg.to_string())),
+                Arg::REPEATMODE(_) => Arg::REPEATMODE(Some(RepeatMode::parse(arg)?)),
             };
             processed_args.push(processed_arg);
         }
@@ -200,4 +244,30 @@ pub enum CommandAction {
     Vol(i64),
     Scroll(i16),
     CopyVideoURL,
+    ExportM3U8,
+    ExportJSON,
+    ExportM3U8To(String),
+    ExportJSONTo(String),
+    LoadHistory(usize),
+    Seek(SeekTarget),
+    QueueSelect(i16),
+    QueueJump,
+    QueueRemove,
+    QueueMoveUp,
+    QueueMoveDown,
+    QueueClearUpcoming,
+    AddToQueue(String),
+    AddNextToQueue(String),
+    AddPlaylistToQueue(String),
+    Search(String),
+    Repeat(RepeatMode),
+    CycleRepeat,
+    ToggleShuffle,
+    Like,
+    Dislike,
+    Download,
+    SearchSelect(i16),
+    SearchPlay,
+    SearchEnqueue,
+    SearchEnqueueNext,
 }
@@ -1,4 +1,5 @@
 use crate::music_player::libmpv_handlers::LibMpvSignals;
+use crate::music_player::music_player_core::music_source::playlist_file::ExportFormat;
 use crate::music_player::music_player_core::MusicPlayerLogicSignals;
 use crate::music_player::tui::commands::{commands_registry::CommandAction, TuiCommands};
 use crate::music_player::tui::TuiSignals;
@@ -250,6 +251,11 @@ impl TUIUserInputHandler {
                         .send(TuiSignals::UpdateHelpStr(help_str))
                         .unwrap();
                 }
+                if tui_state == TuiState::Lyrics {
+                    mp_logic_signal_send
+                        .send(MusicPlayerLogicSignals::RequestLyrics)
+                        .unwrap();
+                }
                 tui_signal_send
                     .send(TuiSignals::UpdateState(tui_state.clone()))
                     .unwrap();
@@ -267,6 +273,9 @@ impl TUIUserInputHandler {
                 libmpv_signal_send
                     .send(LibMpvSignals::SetVolume(self.volume))
                     .unwrap();
+                mp_logic_signal_send
+                    .send(MusicPlayerLogicSignals::UpdateVolume(self.volume))
+                    .unwrap();
             }
             CommandAction::PlayerNext => {
                 libmpv_signal_send.send(LibMpvSignals::PlayNext).unwrap();
@@ -292,6 +301,152 @@ impl TUIUserInputHandler {
                     .send(MusicPlayerLogicSignals::RequestCurrentVideoURL)
                     .unwrap();
             }
+            CommandAction::ExportM3U8 => {
+                mp_logic_signal_send
+                    .send(MusicPlayerLogicSignals::ExportQueue(
+                        ExportFormat::M3u8,
+                        None,
+                    ))
+                    .unwrap();
+            }
+            CommandAction::ExportJSON => {
+                mp_logic_signal_send
+                    .send(MusicPlayerLogicSignals::ExportQueue(
+                        ExportFormat::Json,
+                        None,
+                    ))
+                    .unwrap();
+            }
+            CommandAction::ExportM3U8To(path) => {
+                mp_logic_signal_send
+                    .send(MusicPlayerLogicSignals::ExportQueue(
+                        ExportFormat::M3u8,
+                        Some(path),
+                    ))
+                    .unwrap();
+            }
+            CommandAction::ExportJSONTo(path) => {
+                mp_logic_signal_send
+                    .send(MusicPlayerLogicSignals::ExportQueue(
+                        ExportFormat::Json,
+                        Some(path),
+                    ))
+                    .unwrap();
+            }
+            CommandAction::Seek(target) => {
+                libmpv_signal_send
+                    .send(LibMpvSignals::Seek(target))
+                    .unwrap();
+            }
+            CommandAction::AddToQueue(user_input) => {
+                mp_logic_signal_send
+                    .send(MusicPlayerLogicSignals::AddToQueue(user_input))
+                    .unwrap();
+            }
+            CommandAction::AddNextToQueue(user_input) => {
+                mp_logic_signal_send
+                    .send(MusicPlayerLogicSignals::AddNextToQueue(user_input))
+                    .unwrap();
+            }
+            CommandAction::AddPlaylistToQueue(user_input) => {
+                mp_logic_signal_send
+                    .send(MusicPlayerLogicSignals::AddPlaylistToQueue(user_input))
+                    .unwrap();
+            }
+            CommandAction::Repeat(repeat) => {
+                mp_logic_signal_send
+                    .send(MusicPlayerLogicSignals::SetRepeat(repeat))
+                    .unwrap();
+            }
+            CommandAction::CycleRepeat => {
+                mp_logic_signal_send
+                    .send(MusicPlayerLogicSignals::CycleRepeat)
+                    .unwrap();
+            }
+            CommandAction::ToggleShuffle => {
+                mp_logic_signal_send
+                    .send(MusicPlayerLogicSignals::ToggleShuffle)
+                    .unwrap();
+            }
+            CommandAction::Like => {
+                mp_logic_signal_send
+                    .send(MusicPlayerLogicSignals::Like)
+                    .unwrap();
+            }
+            CommandAction::Dislike => {
+                mp_logic_signal_send
+                    .send(MusicPlayerLogicSignals::Dislike)
+                    .unwrap();
+            }
+            CommandAction::Download => {
+                mp_logic_signal_send
+                    .send(MusicPlayerLogicSignals::DownloadCurrent)
+                    .unwrap();
+            }
+            CommandAction::Search(query) => {
+                mp_logic_signal_send
+                    .send(MusicPlayerLogicSignals::Search(query))
+                    .unwrap();
+                tui_signal_send
+                    .send(TuiSignals::UpdateState(TuiState::Search))
+                    .unwrap();
+                self.tui_state = TuiState::Search;
+            }
+            CommandAction::SearchSelect(offset) => {
+                mp_logic_signal_send
+                    .send(MusicPlayerLogicSignals::SearchSelect(offset))
+                    .unwrap();
+            }
+            CommandAction::SearchPlay => {
+                mp_logic_signal_send
+                    .send(MusicPlayerLogicSignals::SearchPlay)
+                    .unwrap();
+            }
+            CommandAction::SearchEnqueue => {
+                mp_logic_signal_send
+                    .send(MusicPlayerLogicSignals::SearchEnqueue)
+                    .unwrap();
+            }
+            CommandAction::SearchEnqueueNext => {
+                mp_logic_signal_send
+                    .send(MusicPlayerLogicSignals::SearchEnqueueNext)
+                    .unwrap();
+            }
+            CommandAction::QueueSelect(offset) => {
+                mp_logic_signal_send
+                    .send(MusicPlayerLogicSignals::QueueSelect(offset))
+                    .unwrap();
+            }
+            CommandAction::QueueJump => {
+                mp_logic_signal_send
+                    .send(MusicPlayerLogicSignals::QueueJump)
+                    .unwrap();
+            }
+            CommandAction::QueueRemove => {
+                mp_logic_signal_send
+                    .send(MusicPlayerLogicSignals::QueueRemove)
+                    .unwrap();
+            }
+            CommandAction::QueueMoveUp => {
+                mp_logic_signal_send
+                    .send(MusicPlayerLogicSignals::QueueMoveUp)
+                    .unwrap();
+            }
+            CommandAction::QueueMoveDown => {
+                mp_logic_signal_send
+                    .send(MusicPlayerLogicSignals::QueueMoveDown)
+                    .unwrap();
+            }
+            CommandAction::QueueClearUpcoming => {
+                mp_logic_signal_send
+                    .send(MusicPlayerLogicSignals::QueueClearUpcoming)
+                    .unwrap();
+            }
+            CommandAction::LoadHistory(sessions_count) => {
+                mp_logic_signal_send
+                    .send(MusicPlayerLogicSignals::LoadHistory(sessions_count))
+                    .unwrap();
+            }
         }
         false
     }
@@ -2,10 +2,17 @@ use crate::music_player::error::Error;
 
 #[inline(always)]
 pub fn get_reqwest_client() -> reqwest::Result<reqwest::blocking::Client> {
+    get_reqwest_client_with_timeout(std::time::Duration::from_secs(5))
+}
+
+#[inline(always)]
+pub fn get_reqwest_client_with_timeout(
+    timeout: std::time::Duration,
+) -> reqwest::Result<reqwest::blocking::Client> {
     let user_agent: String = format!("{}/{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
 
     let reqwest_client = reqwest::blocking::Client::builder()
-        .timeout(std::time::Duration::from_secs(5))
+        .timeout(timeout)
         .user_agent(user_agent)
         .build()?;
 
@@ -39,6 +46,15 @@ pub fn reqwest_get(url: &str) -> Result<reqwest::blocking::Response, Error> {
     }
 }
 
+pub fn reqwest_download<W: std::io::Write>(url: &str, writer: &mut W) -> Result<u64, Error> {
+    let reqwest_client = get_reqwest_client_with_timeout(std::time::Duration::from_secs(300))?;
+
+    let request = reqwest_client.get(url).build()?;
+    let mut response = reqwest_client.execute(request)?.error_for_status()?;
+
+    Ok(response.copy_to(writer)?)
+}
+
 #[inline(always)]
 pub fn measure_reqwest_get_duration(url: &str) -> reqwest::Result<std::time::Duration> {
     let reqwest_client = get_reqwest_client()?;
@@ -145,3 +161,20 @@ pub fn fetch_invidious_api_domains() -> Result<Vec<String>, Error> {
 
     Ok(invidious_api_domains)
 }
+
+#[cfg(test)]
+pub fn temp_path(name: &str) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!("rustunes_{name}_{}", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let _ = std::fs::remove_dir_all(&path);
+
+    path
+}
+
+#[cfg(test)]
+pub fn temp_dir(name: &str) -> std::path::PathBuf {
+    let dir = temp_path(name);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    dir
+}